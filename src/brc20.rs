@@ -0,0 +1,241 @@
+pub(crate) use self::{
+  brc20_index::{Brc20Index, InscribedOperation, RecentOperation},
+  indexer::{index_brc20, IndexerOptions},
+  metrics::Metrics,
+  owner::Owner,
+  protocol_allowlist::ProtocolAllowlist,
+  ticker::{Brc20Ticker, TickerSummary},
+  ticker_length_policy::{TickerLengthMode, TickerLengthPolicy},
+  tx_info_cache::TxInfoCache,
+  tx_provider::{LocalTxProvider, TxProvider},
+  user_balance::MovementCause,
+  utils::{convert_to_base_units, format_base_units},
+  webhook::Webhook,
+};
+
+use {
+  self::{
+    brc20_tx::{get_owner_of_output, AddressType, Brc20Tx},
+    deploy::{Brc20Deploy, Brc20DeployTx},
+    error::Brc20Error,
+    genesis::{FeeStats, Genesis},
+    invalid_brc20::{InvalidBrc20Tx, InvalidBrc20TxMap},
+    mint::{Brc20Mint, Brc20MintTx},
+    operation::Brc20Operation,
+    transfer::{Brc20Transfer, Brc20TransferTx},
+    user_balance::UserBalance,
+    utils::serialize_base_units,
+    validation_log::ValidationLog,
+    webhook::WebhookEvent,
+  },
+  super::*,
+  bitcoincore_rpc::bitcoincore_rpc_json::GetRawTransactionResult,
+  std::{
+    collections::{BTreeSet, HashMap},
+    ops::RangeInclusive,
+  },
+};
+
+mod brc20_index;
+mod brc20_tx;
+mod deploy;
+mod error;
+mod genesis;
+mod indexer;
+mod invalid_brc20;
+mod metrics;
+mod mint;
+mod operation;
+mod owner;
+mod protocol_allowlist;
+mod ticker;
+mod ticker_length_policy;
+mod transfer;
+mod tx_info_cache;
+mod tx_provider;
+mod user_balance;
+mod utils;
+mod validation_log;
+mod webhook;
+
+const PROTOCOL: &str = "brc-20";
+const DEFAULT_DECIMALS: u8 = 18;
+const MAX_DECIMALS: u8 = 18;
+/// Largest max supply in whole tokens, whatever a ticker's decimals.
+const MAX_SUPPLY: u64 = u64::MAX;
+
+#[cfg(test)]
+fn owner(n: u8) -> Owner {
+  Owner::Address(
+    Address::from_script(
+      &Script::new_v0_p2wpkh(&bitcoin::WPubkeyHash::from_inner([n; 20])),
+      Network::Bitcoin,
+    )
+    .unwrap(),
+  )
+}
+
+#[cfg(test)]
+fn script_pubkey(owner: &Owner) -> Script {
+  match owner {
+    Owner::Address(address) => address.script_pubkey(),
+    Owner::Script(script) => script.clone(),
+  }
+}
+
+#[cfg(test)]
+fn raw_tx_result(n: u64, owner: &Owner, blocktime: usize) -> GetRawTransactionResult {
+  use bitcoincore_rpc::bitcoincore_rpc_json::{
+    GetRawTransactionResultVout, GetRawTransactionResultVoutScriptPubKey,
+  };
+
+  GetRawTransactionResult {
+    in_active_chain: Some(true),
+    hex: Vec::new(),
+    txid: txid(n),
+    hash: bitcoin::Wtxid::all_zeros(),
+    size: 0,
+    vsize: 0,
+    version: 2,
+    locktime: 0,
+    vin: Vec::new(),
+    vout: vec![GetRawTransactionResultVout {
+      value: Amount::from_sat(10_000),
+      n: 0,
+      script_pub_key: GetRawTransactionResultVoutScriptPubKey {
+        asm: String::new(),
+        hex: script_pubkey(owner).to_bytes(),
+        req_sigs: None,
+        type_: None,
+        addresses: Vec::new(),
+        address: match owner {
+          Owner::Address(address) => Some(address.clone()),
+          Owner::Script(_) => None,
+        },
+      },
+    }],
+    blockhash: None,
+    confirmations: Some(1),
+    time: Some(blocktime),
+    blocktime: Some(blocktime),
+  }
+}
+
+#[cfg(test)]
+fn deploy_script(body: &str) -> Option<Brc20Deploy> {
+  match Brc20Operation::parse_all(body).into_iter().next()? {
+    Ok(Brc20Operation::Deploy(deploy_script)) => Some(deploy_script),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+fn mint_script(body: &str) -> Option<Brc20Mint> {
+  match Brc20Operation::parse_all(body).into_iter().next()? {
+    Ok(Brc20Operation::Mint(mint_script)) => Some(mint_script),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+fn transfer_script(body: &str) -> Option<Brc20Transfer> {
+  match Brc20Operation::parse_all(body).into_iter().next()? {
+    Ok(Brc20Operation::Transfer(transfer_script)) => Some(transfer_script),
+    _ => None,
+  }
+}
+
+/// Mined at a height equal to its blocktime, which is enough for tests.
+#[cfg(test)]
+fn brc20_tx(n: u64, owner: &Owner, blocktime: usize) -> Brc20Tx {
+  Brc20Tx::new(&raw_tx_result(n, owner, blocktime), Network::Bitcoin)
+    .unwrap()
+    .with_height(blocktime.try_into().unwrap())
+}
+
+#[cfg(test)]
+fn base_units(amount: &str, decimals: u8) -> u128 {
+  convert_to_base_units(amount, decimals).unwrap()
+}
+
+#[cfg(test)]
+fn sat_point(n: u64) -> SatPoint {
+  SatPoint {
+    outpoint: OutPoint {
+      txid: txid(n),
+      vout: 0,
+    },
+    offset: 0,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn deploy_without_max_is_not_parsed_as_another_operation() {
+    let body = r#"{"p":"brc-20","op":"deploy","tick":"ordi","amt":"1000"}"#;
+    assert_eq!(deploy_script(body), None);
+    assert_eq!(mint_script(body), None);
+    assert_eq!(transfer_script(body), None);
+  }
+
+  #[test]
+  fn script_without_op_is_rejected() {
+    let body = r#"{"p":"brc-20","tick":"ordi","amt":"1000"}"#;
+    assert_eq!(mint_script(body), None);
+    assert_eq!(transfer_script(body), None);
+  }
+
+  #[test]
+  fn op_is_matched_case_insensitively_and_without_whitespace() {
+    assert!(
+      mint_script(r#"{"p":"brc-20","op":" mint ","tick":"ordi","amt":"1"}"#).is_some()
+    );
+    assert!(
+      transfer_script(r#"{"p":"brc-20","op":"TRANSFER","tick":"ordi","amt":"1"}"#)
+        .is_some()
+    );
+    assert!(
+      deploy_script(r#"{"p":"brc-20","op":"Deploy","tick":"ordi","max":"1"}"#).is_some()
+    );
+    assert!(
+      mint_script(r#"{"p":"brc-20","op":"TRANSFER","tick":"ordi","amt":"1"}"#).is_none()
+    );
+  }
+
+  #[test]
+  fn serialized_mints_and_transfers_include_transaction_details() {
+    let brc20_tx = brc20_tx(2, &owner(2), 200);
+
+    let expected = serde_json::json!({
+      "txid": txid(2),
+      "vout": 0,
+      "blocktime": 200,
+      "height": 200,
+      "owner": owner(2),
+      "script_pubkey_type": null,
+    });
+
+    let mint_tx = Brc20MintTx::new(
+      inscription_id(2),
+      2,
+      brc20_tx.clone(),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}"#).unwrap(),
+    );
+    assert_eq!(serde_json::to_value(mint_tx).unwrap()["brc20_tx"], expected);
+
+    let transfer_tx = Brc20TransferTx::new(
+      inscription_id(2),
+      2,
+      brc20_tx,
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"1"}"#)
+        .unwrap(),
+    );
+    assert_eq!(
+      serde_json::to_value(transfer_tx).unwrap()["brc20_tx"],
+      expected
+    );
+  }
+}
@@ -0,0 +1,2012 @@
+use {
+  super::*,
+  std::{cmp::Reverse, collections::btree_map},
+};
+
+/// A valid deploy, mint, or transfer inscription. The amount of a deploy is
+/// its max supply.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct RecentOperation {
+  pub(crate) txid: Txid,
+  pub(crate) op: &'static str,
+  pub(crate) ticker: String,
+  pub(crate) amount: String,
+  pub(crate) owner: Owner,
+  pub(crate) blocktime: u64,
+}
+
+/// A change to the balance of `address`, for building per-address ledgers.
+/// Amounts are in base units of the ticker's decimals.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Movement {
+  pub(crate) address: Owner,
+  pub(crate) ticker: String,
+  pub(crate) decimals: u8,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) amount: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) new_balance: u128,
+  pub(crate) cause: MovementCause,
+  pub(crate) txid: Txid,
+  pub(crate) blocktime: u64,
+}
+
+/// A valid operation inscribed on a sat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum InscribedOperation<'a> {
+  Deploy(&'a Brc20DeployTx),
+  Mint(&'a Brc20MintTx),
+  Transfer(&'a Brc20TransferTx),
+}
+
+/// An operation as it was submitted, kept so that merging shards can
+/// validate it again against the merged state.
+#[derive(Debug, Clone)]
+enum Candidate {
+  Deploy {
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    deploy_script: Brc20Deploy,
+  },
+  Mint {
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    mint_script: Brc20Mint,
+  },
+  Transfer {
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    transfer_script: Brc20Transfer,
+  },
+  Send {
+    tick: String,
+    sender: Owner,
+    spender: Owner,
+    inscription_id: InscriptionId,
+    send_tx: Brc20Tx,
+    sat_point: SatPoint,
+  },
+  /// Rejected before validation, such as a reinscription, so rejected
+  /// again when replayed.
+  Rejected {
+    inscription_id: InscriptionId,
+    op: &'static str,
+    tick: String,
+    brc20_tx: Brc20Tx,
+    err: Brc20Error,
+  },
+}
+
+impl Candidate {
+  fn deploy(deploy_tx: &Brc20DeployTx) -> Self {
+    Self::Deploy {
+      inscription_id: deploy_tx.get_inscription_id(),
+      inscription_number: deploy_tx.get_inscription_number(),
+      brc20_tx: deploy_tx.get_brc20_tx().clone(),
+      deploy_script: deploy_tx.get_deploy_script().clone(),
+    }
+  }
+
+  fn mint(mint_tx: &Brc20MintTx) -> Self {
+    Self::Mint {
+      inscription_id: mint_tx.get_inscription_id(),
+      inscription_number: mint_tx.get_inscription_number(),
+      brc20_tx: mint_tx.get_brc20_tx().clone(),
+      mint_script: mint_tx.get_mint_script().clone(),
+    }
+  }
+
+  fn transfer(transfer_tx: &Brc20TransferTx) -> Self {
+    Self::Transfer {
+      inscription_id: transfer_tx.get_inscription_id(),
+      inscription_number: transfer_tx.get_inscription_number(),
+      brc20_tx: transfer_tx.get_brc20_tx().clone(),
+      transfer_script: transfer_tx.get_transfer_script().clone(),
+    }
+  }
+
+  /// The send of a completed transfer. The shard already checked that the
+  /// sender spent the transfer.
+  fn send(transfer_tx: &Brc20TransferTx) -> Option<Self> {
+    let sender = transfer_tx.get_brc20_tx().get_owner();
+
+    Some(Self::Send {
+      tick: transfer_tx.get_ticker(),
+      sender: sender.clone(),
+      spender: sender.clone(),
+      inscription_id: transfer_tx.get_inscription_id(),
+      send_tx: transfer_tx.get_send_tx()?.clone(),
+      sat_point: transfer_tx.get_sat_point(),
+    })
+  }
+
+  /// The inscription carrying the operation, or, for a send, the transfer
+  /// inscription it sends.
+  fn inscription_id(&self) -> InscriptionId {
+    match self {
+      Self::Deploy { inscription_id, .. }
+      | Self::Mint { inscription_id, .. }
+      | Self::Transfer { inscription_id, .. }
+      | Self::Send { inscription_id, .. }
+      | Self::Rejected { inscription_id, .. } => *inscription_id,
+    }
+  }
+
+  fn inscription_number(&self) -> Option<i64> {
+    match self {
+      Self::Deploy {
+        inscription_number, ..
+      }
+      | Self::Mint {
+        inscription_number, ..
+      }
+      | Self::Transfer {
+        inscription_number, ..
+      } => Some(*inscription_number),
+      Self::Send { .. } | Self::Rejected { .. } => None,
+    }
+  }
+
+  /// The operation rejected for being inscribed on a sat that already
+  /// carries a BRC-20 inscription. Sends aren't inscribed, so have none.
+  fn reinscribed(self) -> Option<Self> {
+    let (inscription_id, op, tick, brc20_tx) = match self {
+      Self::Deploy {
+        inscription_id,
+        brc20_tx,
+        deploy_script,
+        ..
+      } => (inscription_id, "deploy", deploy_script.tick, brc20_tx),
+      Self::Mint {
+        inscription_id,
+        brc20_tx,
+        mint_script,
+        ..
+      } => (inscription_id, "mint", mint_script.tick, brc20_tx),
+      Self::Transfer {
+        inscription_id,
+        brc20_tx,
+        transfer_script,
+        ..
+      } => (inscription_id, "transfer", transfer_script.tick, brc20_tx),
+      Self::Send { .. } => return None,
+      Self::Rejected {
+        inscription_id,
+        op,
+        tick,
+        brc20_tx,
+        ..
+      } => (inscription_id, op, tick, brc20_tx),
+    };
+
+    Some(Self::Rejected {
+      inscription_id,
+      op,
+      tick,
+      brc20_tx,
+      err: Brc20Error::ReinscriptionIgnored,
+    })
+  }
+
+  /// Candidates are replayed in blocktime order. Sharing a blocktime,
+  /// inscriptions go first, by inscription number, and then sends, by the
+  /// inscription number of their transfer, looked up in
+  /// `transfer_numbers`. Operations of the same inscription go deploys
+  /// first, then mints. Rejections don't change any state, so they go last.
+  fn order(&self, transfer_numbers: &HashMap<InscriptionId, i64>) -> (u64, bool, i64, u8) {
+    match self {
+      Self::Deploy {
+        inscription_number,
+        brc20_tx,
+        ..
+      } => (brc20_tx.get_blocktime(), false, *inscription_number, 0),
+      Self::Mint {
+        inscription_number,
+        brc20_tx,
+        ..
+      } => (brc20_tx.get_blocktime(), false, *inscription_number, 1),
+      Self::Transfer {
+        inscription_number,
+        brc20_tx,
+        ..
+      } => (brc20_tx.get_blocktime(), false, *inscription_number, 2),
+      Self::Send {
+        inscription_id,
+        send_tx,
+        ..
+      } => (
+        send_tx.get_blocktime(),
+        true,
+        transfer_numbers
+          .get(inscription_id)
+          .copied()
+          .unwrap_or_default(),
+        0,
+      ),
+      Self::Rejected { brc20_tx, .. } => (brc20_tx.get_blocktime(), true, i64::MAX, u8::MAX),
+    }
+  }
+
+  fn apply(self, brc20_index: &mut Brc20Index) {
+    match self {
+      Self::Deploy {
+        inscription_id,
+        inscription_number,
+        brc20_tx,
+        deploy_script,
+      } => {
+        brc20_index.process_deploy(inscription_id, inscription_number, brc20_tx, deploy_script);
+      }
+      Self::Mint {
+        inscription_id,
+        inscription_number,
+        brc20_tx,
+        mint_script,
+      } => {
+        brc20_index.process_mint(inscription_id, inscription_number, brc20_tx, mint_script);
+      }
+      Self::Transfer {
+        inscription_id,
+        inscription_number,
+        brc20_tx,
+        transfer_script,
+      } => {
+        brc20_index.process_transfer(
+          inscription_id,
+          inscription_number,
+          brc20_tx,
+          transfer_script,
+        );
+      }
+      Self::Send {
+        tick,
+        sender,
+        spender,
+        inscription_id,
+        send_tx,
+        sat_point,
+      } => {
+        brc20_index.process_transfer_send(
+          &tick,
+          &sender,
+          &spender,
+          inscription_id,
+          send_tx,
+          sat_point,
+        );
+      }
+      Self::Rejected {
+        inscription_id,
+        op,
+        tick,
+        brc20_tx,
+        err,
+      } => {
+        brc20_index.reject_operation(inscription_id, op, &tick, brc20_tx, err);
+      }
+    }
+  }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Brc20Index {
+  tickers: HashMap<String, Brc20Ticker>,
+  invalid_tx_map: InvalidBrc20TxMap,
+  skipped_count: usize,
+  skipped: Option<Vec<(InscriptionId, String)>>,
+  missing_inscription_count: usize,
+  genesis: HashMap<InscriptionId, Genesis>,
+  #[serde(skip)]
+  sat_points: BTreeMap<SatPoint, InscriptionId>,
+  #[serde(skip)]
+  rejected: Vec<Candidate>,
+  #[serde(skip)]
+  validation_log: Option<ValidationLog>,
+  #[serde(skip)]
+  webhook: Option<Webhook>,
+  #[serde(skip)]
+  ticker_length_policy: TickerLengthPolicy,
+  #[serde(skip)]
+  protocols: ProtocolAllowlist,
+  #[serde(skip)]
+  default_decimals: Option<u8>,
+}
+
+impl Brc20Index {
+  pub(crate) fn new() -> Self {
+    Self::default()
+  }
+
+  pub(crate) fn with_validation_log(validation_log: ValidationLog) -> Self {
+    Self {
+      validation_log: Some(validation_log),
+      ..Self::default()
+    }
+  }
+
+  pub(crate) fn set_ticker_length_policy(&mut self, ticker_length_policy: TickerLengthPolicy) {
+    self.ticker_length_policy = ticker_length_policy;
+  }
+
+  pub(crate) fn set_protocols(&mut self, protocols: ProtocolAllowlist) {
+    self.protocols = protocols;
+  }
+
+  /// Decimals of tickers deployed without `dec`, `DEFAULT_DECIMALS` unless
+  /// set.
+  pub(crate) fn set_default_decimals(&mut self, default_decimals: u8) {
+    self.default_decimals = Some(default_decimals);
+  }
+
+  pub(crate) fn send_events_to(&mut self, webhook: Webhook) {
+    self.webhook = Some(webhook);
+  }
+
+  /// Keeps skipped inscriptions and their skip reasons, instead of only
+  /// counting them.
+  pub(crate) fn record_skipped(&mut self) {
+    self.skipped.get_or_insert_with(Vec::new);
+  }
+
+  pub(crate) fn add_skipped(&mut self, inscription_id: InscriptionId, reason: String) {
+    self.skipped_count += 1;
+
+    if let Some(skipped) = &mut self.skipped {
+      skipped.push((inscription_id, reason));
+    }
+  }
+
+  pub(crate) fn add_missing_inscription(&mut self) {
+    self.missing_inscription_count += 1;
+  }
+
+  pub(crate) fn record_genesis(&mut self, inscription_id: InscriptionId, genesis: Genesis) {
+    self.genesis.insert(inscription_id, genesis);
+  }
+
+  pub(crate) fn get_genesis(&self, inscription_id: InscriptionId) -> Option<&Genesis> {
+    self.genesis.get(&inscription_id)
+  }
+
+  /// Merges `other`, indexed from a separate block range, into this index.
+  /// The operations of both, including those a shard rejected, are
+  /// replayed in blocktime order and validated again, so the earliest
+  /// deploy of a ticker wins, operations on a ticker deployed in another
+  /// shard are accepted, and balances are recomputed from scratch. A sat
+  /// claimed in both goes to the inscription with the lower number, and the
+  /// other is ignored as a reinscription.
+  pub(crate) fn merge(&mut self, other: Brc20Index) {
+    let validation_log = self.validation_log.take();
+    let webhook = self.webhook.take();
+    let ticker_length_policy = std::mem::take(&mut self.ticker_length_policy);
+    let protocols = std::mem::take(&mut self.protocols);
+    let default_decimals = self.default_decimals;
+    let mut shards = [std::mem::take(self), other];
+    self.ticker_length_policy = ticker_length_policy;
+    self.protocols = protocols;
+    self.default_decimals = default_decimals;
+
+    let mut candidates = Vec::new();
+
+    for shard in &mut shards {
+      for ticker in shard.tickers.values() {
+        candidates.push(Candidate::deploy(ticker.get_deploy_tx()));
+        candidates.extend(ticker.get_mints().iter().map(Candidate::mint));
+
+        for transfer_tx in ticker.get_transfers() {
+          candidates.push(Candidate::transfer(transfer_tx));
+          candidates.extend(Candidate::send(transfer_tx));
+        }
+      }
+
+      candidates.append(&mut shard.rejected);
+    }
+
+    let numbers = candidates
+      .iter()
+      .filter_map(|candidate| Some((candidate.inscription_id(), candidate.inscription_number()?)))
+      .collect::<HashMap<InscriptionId, i64>>();
+
+    let number =
+      |inscription_id: &InscriptionId| numbers.get(inscription_id).copied().unwrap_or(i64::MAX);
+
+    let mut reinscriptions = HashSet::new();
+
+    for shard in &mut shards {
+      for (sat_point, inscription_id) in std::mem::take(&mut shard.sat_points) {
+        match self.sat_points.entry(sat_point) {
+          btree_map::Entry::Vacant(entry) => {
+            entry.insert(inscription_id);
+          }
+          btree_map::Entry::Occupied(mut entry) => {
+            if number(&inscription_id) < number(entry.get()) {
+              reinscriptions.insert(entry.insert(inscription_id));
+            } else {
+              reinscriptions.insert(inscription_id);
+            }
+          }
+        }
+      }
+    }
+
+    // Only the first operation of a reinscription is recorded, as when it's
+    // found by a single shard.
+    let mut reinscribed = HashSet::new();
+
+    let mut candidates = candidates
+      .into_iter()
+      .filter_map(|candidate| {
+        let inscription_id = candidate.inscription_id();
+
+        if !reinscriptions.contains(&inscription_id) {
+          return Some(candidate);
+        }
+
+        let candidate = candidate.reinscribed()?;
+        reinscribed.insert(inscription_id).then_some(candidate)
+      })
+      .collect::<Vec<Candidate>>();
+
+    let transfer_numbers = candidates
+      .iter()
+      .filter_map(|candidate| match candidate {
+        Candidate::Transfer {
+          inscription_id,
+          inscription_number,
+          ..
+        } => Some((*inscription_id, *inscription_number)),
+        _ => None,
+      })
+      .collect::<HashMap<InscriptionId, i64>>();
+
+    candidates.sort_by_key(|candidate| candidate.order(&transfer_numbers));
+
+    for candidate in candidates {
+      candidate.apply(self);
+    }
+
+    for shard in shards {
+      self.skipped_count += shard.skipped_count;
+      self.missing_inscription_count += shard.missing_inscription_count;
+      self.genesis.extend(shard.genesis);
+
+      if let Some(skipped) = shard.skipped {
+        self.skipped.get_or_insert_with(Vec::new).extend(skipped);
+      }
+    }
+
+    self.validation_log = validation_log;
+    self.webhook = webhook;
+  }
+
+  /// Claims the sat at `sat_point` for `inscription_id`. Only the first
+  /// BRC-20 inscription on a sat counts, so a later inscription on the same
+  /// sat is recorded as an invalid `op` on `tick` and `false` is returned.
+  pub(crate) fn claim_sat(
+    &mut self,
+    inscription_id: InscriptionId,
+    op: &'static str,
+    tick: &str,
+    brc20_tx: Brc20Tx,
+    sat_point: SatPoint,
+  ) -> bool {
+    if let btree_map::Entry::Vacant(entry) = self.sat_points.entry(sat_point) {
+      entry.insert(inscription_id);
+      return true;
+    }
+
+    self.reject_operation(
+      inscription_id,
+      op,
+      tick,
+      brc20_tx,
+      Brc20Error::ReinscriptionIgnored,
+    );
+    false
+  }
+
+  /// Records an operation that was rejected before validation, such as a
+  /// transfer inscribed in an array.
+  pub(crate) fn reject_operation(
+    &mut self,
+    inscription_id: InscriptionId,
+    op: &'static str,
+    tick: &str,
+    brc20_tx: Brc20Tx,
+    err: Brc20Error,
+  ) {
+    self.record(op, tick, &brc20_tx, Err(&err));
+    self.add_invalid_tx(inscription_id, op, tick, brc20_tx.clone(), err.clone());
+    self.rejected.push(Candidate::Rejected {
+      inscription_id,
+      op,
+      tick: tick.into(),
+      brc20_tx,
+      err,
+    });
+  }
+
+  /// Keeps the send of a transfer that was rejected, so that merging shards
+  /// can apply it if the transfer turns out to be valid.
+  pub(crate) fn keep_rejected_send(
+    &mut self,
+    tick: &str,
+    sender: &Owner,
+    spender: &Owner,
+    inscription_id: InscriptionId,
+    send_tx: Brc20Tx,
+    sat_point: SatPoint,
+  ) {
+    self.rejected.push(Candidate::Send {
+      tick: tick.into(),
+      sender: sender.clone(),
+      spender: spender.clone(),
+      inscription_id,
+      send_tx,
+      sat_point,
+    });
+  }
+
+  pub(crate) fn process_deploy(
+    &mut self,
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    deploy_script: Brc20Deploy,
+  ) -> bool {
+    let tick = deploy_script.tick.clone();
+
+    // A deploy processed again, for example when reindexing, isn't a second
+    // deploy of its ticker, and leaves the ticker as it is.
+    if self
+      .get_ticker(&tick)
+      .map(|ticker| ticker.get_deploy_tx().get_inscription_id() == inscription_id)
+      .unwrap_or_default()
+    {
+      return true;
+    }
+
+    let candidate = Candidate::Deploy {
+      inscription_id,
+      inscription_number,
+      brc20_tx: brc20_tx.clone(),
+      deploy_script: deploy_script.clone(),
+    };
+
+    let result = self.protocols.check(&deploy_script.p).and_then(|()| {
+      Brc20DeployTx::new(
+        inscription_id,
+        inscription_number,
+        brc20_tx.clone(),
+        deploy_script,
+        self.default_decimals.unwrap_or(DEFAULT_DECIMALS),
+      )
+      .validate_deploy_script(&self.tickers, &self.ticker_length_policy)
+    });
+
+    match result {
+      Ok(deploy_tx) => {
+        self.record("deploy", &tick, &brc20_tx, Ok(()));
+        if let Some(webhook) = &self.webhook {
+          webhook.notify(WebhookEvent::deploy(&deploy_tx));
+        }
+        self
+          .tickers
+          .insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+        true
+      }
+      Err(err) => {
+        self.record("deploy", &tick, &brc20_tx, Err(&err));
+        self.add_invalid_tx(inscription_id, "deploy", &tick, brc20_tx, err);
+        self.rejected.push(candidate);
+        false
+      }
+    }
+  }
+
+  pub(crate) fn process_mint(
+    &mut self,
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    mint_script: Brc20Mint,
+  ) -> bool {
+    let tick = mint_script.tick.clone();
+
+    let candidate = Candidate::Mint {
+      inscription_id,
+      inscription_number,
+      brc20_tx: brc20_tx.clone(),
+      mint_script: mint_script.clone(),
+    };
+
+    let result = self.protocols.check(&mint_script.p).and_then(|()| {
+      Brc20MintTx::new(
+        inscription_id,
+        inscription_number,
+        brc20_tx.clone(),
+        mint_script,
+      )
+      .validate(&self.tickers)
+    });
+
+    match result {
+      Ok(mint_tx) => {
+        self.record("mint", &tick, &brc20_tx, Ok(()));
+        if let Some(ticker) = self.tickers.get_mut(&mint_tx.get_ticker()) {
+          if let Some(webhook) = &self.webhook {
+            webhook.notify(WebhookEvent::mint(ticker, &mint_tx));
+          }
+          ticker.add_mint(mint_tx);
+        }
+        true
+      }
+      Err(err) => {
+        self.record("mint", &tick, &brc20_tx, Err(&err));
+        self.add_invalid_tx(inscription_id, "mint", &tick, brc20_tx, err);
+        self.rejected.push(candidate);
+        false
+      }
+    }
+  }
+
+  pub(crate) fn process_transfer(
+    &mut self,
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    transfer_script: Brc20Transfer,
+  ) -> bool {
+    let tick = transfer_script.tick.clone();
+
+    let candidate = Candidate::Transfer {
+      inscription_id,
+      inscription_number,
+      brc20_tx: brc20_tx.clone(),
+      transfer_script: transfer_script.clone(),
+    };
+
+    let result = self.protocols.check(&transfer_script.p).and_then(|()| {
+      Brc20TransferTx::new(
+        inscription_id,
+        inscription_number,
+        brc20_tx.clone(),
+        transfer_script,
+      )
+      .handle_inscribe_transfer_amount(&mut self.tickers)
+    });
+
+    match result {
+      Ok(transfer_tx) => {
+        self.record("transfer", &tick, &brc20_tx, Ok(()));
+        if let Some(ticker) = self.tickers.get_mut(&transfer_tx.get_ticker()) {
+          if let Some(webhook) = &self.webhook {
+            webhook.notify(WebhookEvent::transfer(ticker, &transfer_tx));
+          }
+          ticker.add_transfer(transfer_tx);
+        }
+        true
+      }
+      Err(err) => {
+        self.record("transfer", &tick, &brc20_tx, Err(&err));
+        self.add_invalid_tx(inscription_id, "transfer", &tick, brc20_tx, err);
+        self.rejected.push(candidate);
+        false
+      }
+    }
+  }
+
+  pub(crate) fn process_transfer_send(
+    &mut self,
+    tick: &str,
+    sender: &Owner,
+    spender: &Owner,
+    inscription_id: InscriptionId,
+    send_tx: Brc20Tx,
+    sat_point: SatPoint,
+  ) -> bool {
+    let result = match self.tickers.get_mut(&tick.to_lowercase()) {
+      Some(ticker) => {
+        match ticker.handle_transfer_send(
+          inscription_id,
+          sender,
+          spender,
+          send_tx.clone(),
+          sat_point,
+        ) {
+          Ok(Some(transfer_tx)) => {
+            if let Some(webhook) = &self.webhook {
+              webhook.notify(WebhookEvent::transfer(ticker, &transfer_tx));
+            }
+            Ok(true)
+          }
+          Ok(None) => Ok(false),
+          Err(err) => Err(err),
+        }
+      }
+      None => Ok(false),
+    };
+
+    match result {
+      Ok(true) => {
+        self.record("send", tick, &send_tx, Ok(()));
+        return true;
+      }
+      Ok(false) => {}
+      Err(err) => {
+        self.record("send", tick, &send_tx, Err(&err));
+        self.add_invalid_tx(inscription_id, "send", tick, send_tx.clone(), err);
+      }
+    }
+
+    self.keep_rejected_send(tick, sender, spender, inscription_id, send_tx, sat_point);
+    false
+  }
+
+  fn record(&self, op: &str, tick: &str, brc20_tx: &Brc20Tx, result: Result<(), &Brc20Error>) {
+    if let Some(validation_log) = &self.validation_log {
+      validation_log.record(brc20_tx.get_txid(), op, tick, result);
+    }
+  }
+
+  fn add_invalid_tx(
+    &mut self,
+    inscription_id: InscriptionId,
+    op: &'static str,
+    tick: &str,
+    brc20_tx: Brc20Tx,
+    err: Brc20Error,
+  ) {
+    self.invalid_tx_map.add_invalid_tx(InvalidBrc20Tx::new(
+      inscription_id,
+      op,
+      tick.into(),
+      brc20_tx,
+      err.to_string(),
+    ));
+  }
+
+  pub(crate) fn get_ticker(&self, tick: &str) -> Option<&Brc20Ticker> {
+    self.tickers.get(&tick.to_lowercase())
+  }
+
+  /// Tickers in deploy order, by the inscription number of their deploy.
+  pub(crate) fn tickers(&self) -> impl Iterator<Item = &Brc20Ticker> {
+    let mut tickers = self.tickers.values().collect::<Vec<&Brc20Ticker>>();
+    tickers.sort_by_key(|ticker| ticker.get_deploy_tx().get_inscription_number());
+    tickers.into_iter()
+  }
+
+  /// Ticker summaries, most minted first. Tickers minted to the same
+  /// percentage stay in deploy order.
+  pub(crate) fn list_tickers(&self) -> Vec<TickerSummary> {
+    let mut summaries = self
+      .tickers()
+      .map(Brc20Ticker::summary)
+      .collect::<Vec<TickerSummary>>();
+    summaries.sort_by(|a, b| b.percent_minted.total_cmp(&a.percent_minted));
+    summaries
+  }
+
+  /// Size and fee stats of the valid deploy, mint, and transfer inscriptions
+  /// of `tick`.
+  pub(crate) fn fee_stats(&self, tick: &str) -> Option<FeeStats> {
+    let ticker = self.get_ticker(tick)?;
+
+    let inscription_ids = std::iter::once(ticker.get_deploy_tx().get_inscription_id())
+      .chain(
+        ticker
+          .get_mints()
+          .iter()
+          .map(|mint_tx| mint_tx.get_inscription_id()),
+      )
+      .chain(
+        ticker
+          .get_transfers()
+          .iter()
+          .map(|transfer_tx| transfer_tx.get_inscription_id()),
+      );
+
+    Some(FeeStats::new(
+      inscription_ids.filter_map(|inscription_id| self.get_genesis(inscription_id)),
+    ))
+  }
+
+  /// The `limit` most recent operations, latest blocktime first. Operations
+  /// sharing a blocktime stay in deploy, mint, transfer order per ticker.
+  pub(crate) fn recent_operations(&self, limit: usize) -> Vec<RecentOperation> {
+    let mut operations = Vec::new();
+
+    for ticker in self.tickers() {
+      let decimals = ticker.get_decimals();
+
+      let operation = |op, brc20_tx: &Brc20Tx, amount| RecentOperation {
+        txid: brc20_tx.get_txid(),
+        op,
+        ticker: ticker.get_display_ticker().into(),
+        amount: format_base_units(amount, decimals),
+        owner: brc20_tx.get_owner().clone(),
+        blocktime: brc20_tx.get_blocktime(),
+      };
+
+      let deploy_tx = ticker.get_deploy_tx();
+      operations.push(operation(
+        "deploy",
+        deploy_tx.get_brc20_tx(),
+        deploy_tx.get_max_supply(),
+      ));
+
+      for mint_tx in ticker.get_mints() {
+        operations.push(operation(
+          "mint",
+          mint_tx.get_brc20_tx(),
+          mint_tx.get_amount(),
+        ));
+      }
+
+      for transfer_tx in ticker.get_transfers() {
+        operations.push(operation(
+          "transfer",
+          transfer_tx.get_brc20_tx(),
+          transfer_tx.get_amount(),
+        ));
+      }
+    }
+
+    operations.sort_by_key(|operation| Reverse(operation.blocktime));
+    operations.truncate(limit);
+    operations
+  }
+
+  /// Balance movements of every holder of every ticker, ordered by
+  /// blocktime and txid, with the send of a transfer before its receive.
+  pub(crate) fn movements(&self) -> Vec<Movement> {
+    let mut movements = Vec::new();
+
+    for ticker in self.tickers() {
+      for (address, user_balance) in ticker.get_balances() {
+        movements.extend(
+          user_balance
+            .get_movements()
+            .iter()
+            .map(|movement| Movement {
+              address: address.clone(),
+              ticker: ticker.get_display_ticker().into(),
+              decimals: ticker.get_decimals(),
+              amount: movement.amount,
+              new_balance: movement.new_balance,
+              cause: movement.cause,
+              txid: movement.txid,
+              blocktime: movement.blocktime,
+            }),
+        );
+      }
+    }
+
+    movements.sort_by_key(|movement| (movement.blocktime, movement.txid, movement.cause));
+    movements
+  }
+
+  /// The valid operation of the inscription that claimed the sat at
+  /// `sat_point`, located as of indexing. An inscription carrying several
+  /// operations resolves to the first found, in deploy order of tickers.
+  pub(crate) fn inscription_at(&self, sat_point: SatPoint) -> Option<InscribedOperation<'_>> {
+    let inscription_id = *self.sat_points.get(&sat_point)?;
+
+    for ticker in self.tickers() {
+      if ticker.get_deploy_tx().get_inscription_id() == inscription_id {
+        return Some(InscribedOperation::Deploy(ticker.get_deploy_tx()));
+      }
+
+      if let Some(mint_tx) = ticker
+        .get_mints()
+        .iter()
+        .find(|mint_tx| mint_tx.get_inscription_id() == inscription_id)
+      {
+        return Some(InscribedOperation::Mint(mint_tx));
+      }
+
+      if let Some(transfer_tx) = ticker
+        .get_transfers()
+        .iter()
+        .find(|transfer_tx| transfer_tx.get_inscription_id() == inscription_id)
+      {
+        return Some(InscribedOperation::Transfer(transfer_tx));
+      }
+    }
+
+    None
+  }
+
+  pub(crate) fn ticker_count(&self) -> usize {
+    self.tickers.len()
+  }
+
+  pub(crate) fn get_invalid_tx_map(&self) -> &InvalidBrc20TxMap {
+    &self.invalid_tx_map
+  }
+
+  pub(crate) fn get_skipped_count(&self) -> usize {
+    self.skipped_count
+  }
+
+  pub(crate) fn get_skipped(&self) -> Option<&[(InscriptionId, String)]> {
+    self.skipped.as_deref()
+  }
+
+  /// Inscriptions listed by number that couldn't be found by id, which are
+  /// also counted as skipped.
+  pub(crate) fn get_missing_inscription_count(&self) -> usize {
+    self.missing_inscription_count
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn deploy(brc20_index: &mut Brc20Index, n: u32, tick: &str) -> bool {
+    brc20_index.process_deploy(
+      inscription_id(n),
+      n.into(),
+      brc20_tx(n.into(), &owner(1), 100),
+      deploy_script(&format!(
+        r#"{{"p":"brc-20","op":"deploy","tick":"{tick}","max":"1000"}}"#
+      ))
+      .unwrap(),
+    )
+  }
+
+  /// Why the first rejected operation of inscription `n` was rejected.
+  fn rejection(brc20_index: &Brc20Index, n: u32) -> &str {
+    brc20_index
+      .get_invalid_tx_map()
+      .iter()
+      .find(|invalid_tx| invalid_tx.get_inscription_id() == inscription_id(n))
+      .unwrap()
+      .get_reason()
+  }
+
+  #[test]
+  fn get_ticker_is_case_insensitive() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "Ordi"));
+
+    assert_eq!(brc20_index.get_ticker("ordi").unwrap().get_ticker(), "ordi");
+    assert_eq!(brc20_index.get_ticker("ORDI").unwrap().get_ticker(), "ordi");
+    assert_eq!(brc20_index.get_ticker("OrDi").unwrap().get_ticker(), "ordi");
+    assert!(brc20_index.get_ticker("sats").is_none());
+  }
+
+  #[test]
+  fn display_ticker_keeps_deployed_casing() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "Ordi"));
+
+    let ticker = brc20_index.get_ticker("ORDI").unwrap();
+    assert_eq!(ticker.get_ticker(), "ordi");
+    assert_eq!(ticker.get_display_ticker(), "Ordi");
+    assert_eq!(brc20_index.list_tickers()[0].ticker, "Ordi");
+    assert_eq!(brc20_index.recent_operations(1)[0].ticker, "Ordi");
+  }
+
+  #[test]
+  fn tickers_and_ticker_count() {
+    let mut brc20_index = Brc20Index::new();
+    assert_eq!(brc20_index.ticker_count(), 0);
+
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    assert!(deploy(&mut brc20_index, 2, "sats"));
+
+    assert_eq!(brc20_index.ticker_count(), 2);
+
+    assert_eq!(
+      brc20_index
+        .tickers()
+        .map(|ticker| ticker.get_ticker())
+        .collect::<Vec<&str>>(),
+      ["ordi", "sats"]
+    );
+  }
+
+  #[test]
+  fn tickers_are_in_deploy_order() {
+    let mut brc20_index = Brc20Index::new();
+
+    assert!(deploy(&mut brc20_index, 7, "sats"));
+    assert!(deploy(&mut brc20_index, 2, "ordi"));
+    assert!(deploy(&mut brc20_index, 5, "pepe"));
+
+    assert_eq!(
+      brc20_index
+        .tickers()
+        .map(|ticker| (
+          ticker.get_ticker(),
+          ticker.get_deploy_tx().get_inscription_number()
+        ))
+        .collect::<Vec<(&str, i64)>>(),
+      [("ordi", 2), ("pepe", 5), ("sats", 7)]
+    );
+  }
+
+  #[test]
+  fn list_tickers_is_sorted_by_percent_minted() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    assert!(deploy(&mut brc20_index, 2, "sats"));
+    assert!(deploy(&mut brc20_index, 3, "pepe"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(4),
+      4,
+      brc20_tx(4, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"sats","amt":"100"}"#).unwrap(),
+    ));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(5),
+      5,
+      brc20_tx(5, &owner(3), 300),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"pepe","amt":"500"}"#).unwrap(),
+    ));
+
+    assert_eq!(
+      brc20_index.list_tickers(),
+      [
+        TickerSummary {
+          ticker: "pepe".into(),
+          decimals: 18,
+          max_supply: base_units("1000", 18),
+          total_minted: base_units("500", 18),
+          percent_minted: 50.0,
+          holders: 1,
+          deploy_blocktime: 100,
+        },
+        TickerSummary {
+          ticker: "sats".into(),
+          decimals: 18,
+          max_supply: base_units("1000", 18),
+          total_minted: base_units("100", 18),
+          percent_minted: 10.0,
+          holders: 1,
+          deploy_blocktime: 100,
+        },
+        TickerSummary {
+          ticker: "ordi".into(),
+          decimals: 18,
+          max_supply: base_units("1000", 18),
+          total_minted: 0,
+          percent_minted: 0.0,
+          holders: 0,
+          deploy_blocktime: 100,
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn inscription_at_resolves_claimed_sat_to_its_operation() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(brc20_index.claim_sat(
+      inscription_id(1),
+      "mint",
+      "ordi",
+      brc20_tx(1, &owner(1), 100),
+      sat_point(1)
+    ));
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert!(brc20_index.claim_sat(
+      inscription_id(2),
+      "mint",
+      "ordi",
+      brc20_tx(2, &owner(2), 200),
+      sat_point(2)
+    ));
+    mint(&mut brc20_index, 2, &owner(2), 200, "100");
+
+    assert!(brc20_index.claim_sat(
+      inscription_id(3),
+      "mint",
+      "ordi",
+      brc20_tx(3, &owner(2), 300),
+      sat_point(3)
+    ));
+    assert!(!brc20_index.process_mint(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 300),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1001"}"#).unwrap(),
+    ));
+
+    assert!(matches!(
+      brc20_index.inscription_at(sat_point(1)),
+      Some(InscribedOperation::Deploy(deploy_tx)) if deploy_tx.get_ticker() == "ordi"
+    ));
+
+    let Some(InscribedOperation::Mint(mint_tx)) = brc20_index.inscription_at(sat_point(2)) else {
+      panic!("expected mint at {}", sat_point(2));
+    };
+    assert_eq!(mint_tx.get_inscription_id(), inscription_id(2));
+    assert_eq!(mint_tx.get_amount(), base_units("100", 18));
+
+    assert_eq!(brc20_index.inscription_at(sat_point(3)), None);
+    assert_eq!(brc20_index.inscription_at(sat_point(4)), None);
+  }
+
+  #[test]
+  fn protocol_must_be_allowed() {
+    let deploy = |brc20_index: &mut Brc20Index, n: u32, p: &str| {
+      brc20_index.process_deploy(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(1), 100),
+        deploy_script(&format!(
+          r#"{{"p":"{p}","op":"deploy","tick":"ord{n}","max":"1000"}}"#
+        ))
+        .unwrap(),
+      )
+    };
+
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "brc-20"));
+    assert!(deploy(&mut brc20_index, 2, " BRC-20"));
+    assert!(!deploy(&mut brc20_index, 3, "brc20"));
+    assert!(!deploy(&mut brc20_index, 4, "brc-21"));
+    assert_eq!(rejection(&brc20_index, 3), "Invalid protocol: brc20");
+
+    let mut brc20_index = Brc20Index::new();
+    brc20_index.set_protocols(ProtocolAllowlist::new(["brc-20", "BRC20"]));
+    assert!(deploy(&mut brc20_index, 1, "brc-20"));
+    assert!(deploy(&mut brc20_index, 3, "brc20"));
+    assert!(!deploy(&mut brc20_index, 4, "brc-21"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(5),
+      5,
+      brc20_tx(5, &owner(2), 200),
+      mint_script(r#"{"p":"brc20","op":"mint","tick":"ord1","amt":"10"}"#).unwrap(),
+    ));
+    assert!(!brc20_index.process_transfer(
+      inscription_id(6),
+      6,
+      brc20_tx(6, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-21","op":"transfer","tick":"ord1","amt":"5"}"#).unwrap(),
+    ));
+    assert_eq!(rejection(&brc20_index, 6), "Invalid protocol: brc-21");
+  }
+
+  #[test]
+  fn mint_and_send_produce_movements() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &owner(2), 200, "100");
+    transfer(&mut brc20_index, 3, &owner(2), 300, "40");
+    assert!(brc20_index.process_transfer_send(
+      "ordi",
+      &owner(2),
+      &owner(2),
+      inscription_id(3),
+      brc20_tx(4, &owner(3), 400),
+      sat_point(4),
+    ));
+
+    let movement = |address, amount, new_balance, cause, n, blocktime| Movement {
+      address,
+      ticker: "ordi".into(),
+      decimals: 18,
+      amount: base_units(amount, 18),
+      new_balance: base_units(new_balance, 18),
+      cause,
+      txid: txid(n),
+      blocktime,
+    };
+
+    assert_eq!(
+      brc20_index.movements(),
+      [
+        movement(owner(2), "100", "100", MovementCause::Mint, 2, 200),
+        movement(owner(2), "40", "60", MovementCause::Send, 4, 400),
+        movement(owner(3), "40", "40", MovementCause::Receive, 4, 400),
+      ]
+    );
+  }
+
+  #[test]
+  fn recent_operations_are_latest_first() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+
+    assert!(brc20_index.process_transfer(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"40"}"#).unwrap(),
+    ));
+
+    assert_eq!(
+      brc20_index.recent_operations(2),
+      [
+        RecentOperation {
+          txid: txid(3),
+          op: "transfer",
+          ticker: "ordi".into(),
+          amount: "40.0".into(),
+          owner: owner(2),
+          blocktime: 300,
+        },
+        RecentOperation {
+          txid: txid(2),
+          op: "mint",
+          ticker: "ordi".into(),
+          amount: "100.0".into(),
+          owner: owner(2),
+          blocktime: 200,
+        },
+      ]
+    );
+
+    let operations = brc20_index.recent_operations(10);
+    assert_eq!(operations.len(), 3);
+    assert_eq!(operations[2].op, "deploy");
+    assert_eq!(operations[2].amount, "1000.0");
+  }
+
+  #[test]
+  fn recent_operations_format_amounts_exactly() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(brc20_index.process_deploy(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"20999999999999.999999999999999999"}"#
+      )
+      .unwrap(),
+    ));
+
+    assert_eq!(
+      brc20_index.recent_operations(1)[0].amount,
+      "20999999999999.999999999999999999"
+    );
+  }
+
+  #[test]
+  fn invalid_operations_are_recorded() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    assert!(!deploy(&mut brc20_index, 2, "ordi"));
+
+    assert_eq!(brc20_index.ticker_count(), 1);
+    assert_eq!(rejection(&brc20_index, 2), "Ticker already exists: ordi");
+  }
+
+  #[test]
+  fn reprocessed_deploy_is_not_a_duplicate() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &owner(2), 200, "100");
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert_eq!(brc20_index.ticker_count(), 1);
+    assert_eq!(brc20_index.get_invalid_tx_map().len(), 0);
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_total_minted(), base_units("100", 18));
+    assert_eq!(ticker.get_mints().len(), 1);
+  }
+
+  #[test]
+  fn mint_and_transfer_update_balances() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+
+    assert!(brc20_index.process_transfer(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"40"}"#).unwrap(),
+    ));
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_total_minted(), base_units("100", 18));
+    assert_eq!(ticker.get_mints().len(), 1);
+    assert_eq!(ticker.get_transfers().len(), 1);
+
+    let user_balance = ticker.get_user_balance(&owner(2)).unwrap();
+    assert_eq!(user_balance.get_overall_balance(), base_units("100", 18));
+    assert_eq!(user_balance.get_available_balance(), base_units("60", 18));
+  }
+
+  fn mint(brc20_index: &mut Brc20Index, n: u32, owner: &Owner, blocktime: usize, amt: &str) {
+    assert!(brc20_index.process_mint(
+      inscription_id(n),
+      n.into(),
+      brc20_tx(n.into(), owner, blocktime),
+      mint_script(&format!(
+        r#"{{"p":"brc-20","op":"mint","tick":"ordi","amt":"{amt}"}}"#
+      ))
+      .unwrap(),
+    ));
+  }
+
+  fn transfer(brc20_index: &mut Brc20Index, n: u32, owner: &Owner, blocktime: usize, amt: &str) {
+    assert!(brc20_index.process_transfer(
+      inscription_id(n),
+      n.into(),
+      brc20_tx(n.into(), owner, blocktime),
+      transfer_script(&format!(
+        r#"{{"p":"brc-20","op":"transfer","tick":"ordi","amt":"{amt}"}}"#
+      ))
+      .unwrap(),
+    ));
+  }
+
+  #[test]
+  fn fee_stats_cover_valid_inscriptions_of_ticker() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &owner(2), 200, "100");
+    transfer(&mut brc20_index, 3, &owner(2), 300, "60");
+    assert!(!brc20_index.process_mint(
+      inscription_id(4),
+      4,
+      brc20_tx(4, &owner(2), 400),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"pepe","amt":"1"}"#).unwrap(),
+    ));
+
+    for (n, fee) in [(1, 1_000), (2, 2_000), (3, 3_000), (4, 4_000)] {
+      brc20_index.record_genesis(
+        inscription_id(n),
+        Genesis {
+          size: 50,
+          fee: Some(fee),
+          vsize: 100,
+          fee_rate: Some(fee as f64 / 100.0),
+        },
+      );
+    }
+
+    assert_eq!(
+      brc20_index.fee_stats("ORDI"),
+      Some(FeeStats {
+        inscriptions: 3,
+        total_size: 150,
+        total_fee: 6_000,
+        average_fee_rate: Some(20.0),
+      })
+    );
+    assert_eq!(brc20_index.fee_stats("pepe"), None);
+  }
+
+  #[test]
+  fn transfer_without_holdings_creates_no_balance() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &owner(2), 200, "100");
+
+    assert!(!brc20_index.process_transfer(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(3), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"10"}"#).unwrap(),
+    ));
+
+    assert_eq!(rejection(&brc20_index, 3), "User balance not found");
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_user_balance(&owner(3)), None);
+    assert_eq!(ticker.get_balances().len(), 1);
+    assert_eq!(ticker.stats().holders, 1);
+  }
+
+  #[test]
+  fn balances_can_be_held_by_scripts_without_an_address() {
+    let bare_multisig = Owner::Script(
+      "5121020202020202020202020202020202020202020202020202020202020202020251ae"
+        .parse()
+        .unwrap(),
+    );
+
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &bare_multisig, 200, "100");
+    transfer(&mut brc20_index, 3, &bare_multisig, 300, "40");
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    let user_balance = ticker.get_user_balance(&bare_multisig).unwrap();
+    assert_eq!(user_balance.get_overall_balance(), base_units("100", 18));
+    assert_eq!(user_balance.get_available_balance(), base_units("60", 18));
+    assert_eq!(
+      ticker.get_transfers()[0].get_brc20_tx().get_owner(),
+      &bare_multisig
+    );
+  }
+
+  #[test]
+  fn inscribed_transfers_are_not_circulating() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &owner(2), 200, "100");
+    transfer(&mut brc20_index, 3, &owner(2), 300, "30");
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_total_minted(), base_units("100", 18));
+    assert_eq!(ticker.circulating_supply(), base_units("70", 18));
+    assert_eq!(ticker.stats().circulating_supply, base_units("70", 18));
+  }
+
+  #[test]
+  fn default_decimals_apply_to_deploys_without_dec() {
+    let mut brc20_index = Brc20Index::new();
+    brc20_index.set_default_decimals(0);
+
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    assert!(brc20_index.process_deploy(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"1000","dec":"8"}"#)
+        .unwrap(),
+    ));
+
+    assert_eq!(brc20_index.get_ticker("ordi").unwrap().get_decimals(), 0);
+    assert_eq!(brc20_index.get_ticker("sats").unwrap().get_decimals(), 8);
+
+    mint(&mut brc20_index, 3, &owner(2), 200, "10");
+    assert!(!brc20_index.process_mint(
+      inscription_id(4),
+      4,
+      brc20_tx(4, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1.5"}"#).unwrap(),
+    ));
+
+    assert_eq!(
+      rejection(&brc20_index, 4),
+      Brc20Error::TooManyDecimals { decimals: 0 }.to_string()
+    );
+    assert_eq!(
+      brc20_index.get_ticker("ordi").unwrap().get_total_minted(),
+      base_units("10", 0)
+    );
+  }
+
+  #[test]
+  fn merge_shards_with_interleaved_blocktimes() {
+    let mut first = Brc20Index::new();
+    assert!(deploy(&mut first, 1, "ordi"));
+    mint(&mut first, 2, &owner(2), 200, "100");
+    transfer(&mut first, 3, &owner(2), 400, "60");
+
+    let mut second = Brc20Index::new();
+    assert!(second.process_deploy(
+      inscription_id(5),
+      5,
+      brc20_tx(5, &owner(3), 150),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ORDI","max":"10"}"#).unwrap(),
+    ));
+    mint(&mut second, 6, &owner(3), 300, "10");
+    transfer(&mut second, 7, &owner(3), 500, "10");
+    assert!(second.process_transfer_send(
+      "ordi",
+      &owner(3),
+      &owner(3),
+      inscription_id(7),
+      brc20_tx(8, &owner(2), 500),
+      sat_point(8),
+    ));
+
+    first.merge(second);
+
+    assert_eq!(first.ticker_count(), 1);
+
+    let ticker = first.get_ticker("ordi").unwrap();
+    assert_eq!(
+      ticker.get_deploy_tx().get_inscription_id(),
+      inscription_id(1)
+    );
+    assert_eq!(ticker.get_max_supply(), base_units("1000", 18));
+    assert_eq!(ticker.get_total_minted(), base_units("110", 18));
+    assert_eq!(ticker.get_mints().len(), 2);
+    assert_eq!(ticker.get_transfers().len(), 2);
+
+    let balance = |owner: &Owner| {
+      let user_balance = ticker.get_user_balance(owner).unwrap();
+      (
+        user_balance.get_overall_balance(),
+        user_balance.get_available_balance(),
+      )
+    };
+    assert_eq!(
+      balance(&owner(2)),
+      (base_units("110", 18), base_units("50", 18))
+    );
+    assert_eq!(balance(&owner(3)), (0, 0));
+
+    assert_eq!(
+      rejection(&first, 5),
+      "Ticker already exists: ORDI matches ordi, tickers are case-insensitive"
+    );
+  }
+
+  #[test]
+  fn merge_revalidates_operations_a_shard_rejected() {
+    let mut first = Brc20Index::new();
+    assert!(deploy(&mut first, 1, "ordi"));
+    mint(&mut first, 2, &owner(2), 200, "100");
+
+    let mut second = Brc20Index::new();
+    assert!(!second.process_mint(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(3), 300),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"10"}"#).unwrap(),
+    ));
+    assert!(!second.process_transfer(
+      inscription_id(4),
+      4,
+      brc20_tx(4, &owner(3), 400),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"10"}"#).unwrap(),
+    ));
+    second.keep_rejected_send(
+      "ordi",
+      &owner(3),
+      &owner(3),
+      inscription_id(4),
+      brc20_tx(5, &owner(2), 500),
+      sat_point(5),
+    );
+    assert_eq!(second.get_invalid_tx_map().len(), 2);
+
+    first.merge(second);
+
+    let ticker = first.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_total_minted(), base_units("110", 18));
+    assert_eq!(ticker.get_mints().len(), 2);
+    assert_eq!(ticker.get_transfers().len(), 1);
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("110", 18)
+    );
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(3))
+        .unwrap()
+        .get_overall_balance(),
+      0
+    );
+    assert_eq!(first.get_invalid_tx_map().len(), 0);
+  }
+
+  #[test]
+  fn merge_orders_operations_sharing_a_blocktime_by_inscription_number() {
+    let mut first = Brc20Index::new();
+    assert!(deploy(&mut first, 1, "ordi"));
+    mint(&mut first, 3, &owner(3), 200, "1000");
+
+    let mut second = Brc20Index::new();
+    assert!(!second.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#).unwrap(),
+    ));
+
+    first.merge(second);
+
+    let ticker = first.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_mints().len(), 1);
+    assert_eq!(
+      ticker.get_mints()[0].get_inscription_id(),
+      inscription_id(2)
+    );
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("1000", 18)
+    );
+    assert_eq!(rejection(&first, 3), "Ticker fully minted");
+  }
+
+  #[test]
+  fn merge_ignores_reinscriptions_across_shards() {
+    let mut first = Brc20Index::new();
+    assert!(deploy(&mut first, 1, "ordi"));
+    assert!(first.claim_sat(
+      inscription_id(2),
+      "mint",
+      "ordi",
+      brc20_tx(2, &owner(2), 200),
+      sat_point(9)
+    ));
+    mint(&mut first, 2, &owner(2), 200, "100");
+
+    let mut second = Brc20Index::new();
+    assert!(second.claim_sat(
+      inscription_id(3),
+      "mint",
+      "ordi",
+      brc20_tx(3, &owner(3), 300),
+      sat_point(9)
+    ));
+    assert!(!second.process_mint(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(3), 300),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"10"}"#).unwrap(),
+    ));
+
+    second.merge(first);
+
+    let ticker = second.get_ticker("ordi").unwrap();
+    assert_eq!(ticker.get_total_minted(), base_units("100", 18));
+    assert_eq!(ticker.get_user_balance(&owner(3)), None);
+    assert_eq!(rejection(&second, 3), "Reinscription ignored");
+    assert_eq!(second.get_invalid_tx_map().len(), 1);
+    assert_eq!(
+      second.sat_points.get(&sat_point(9)),
+      Some(&inscription_id(2))
+    );
+  }
+
+  #[test]
+  fn transfers_together_exceeding_balance_are_rejected() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+
+    for n in [3, 4] {
+      assert_eq!(
+        brc20_index.process_transfer(
+          inscription_id(n),
+          n.into(),
+          brc20_tx(n.into(), &owner(2), 300),
+          transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"60"}"#).unwrap(),
+        ),
+        n == 3
+      );
+    }
+
+    assert_eq!(
+      rejection(&brc20_index, 4),
+      "Transfer amount 60.0 ordi exceeds available balance 40.0 ordi"
+    );
+
+    let user_balance = brc20_index
+      .get_ticker("ordi")
+      .unwrap()
+      .get_user_balance(&owner(2))
+      .unwrap();
+    assert_eq!(
+      user_balance.get_transferable_balance(),
+      base_units("60", 18)
+    );
+    assert_eq!(user_balance.get_available_balance(), base_units("40", 18));
+  }
+
+  #[test]
+  fn transfer_send_moves_balance_and_records_ledgers() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+
+    assert!(brc20_index.process_transfer(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ORDI","amt":"40"}"#).unwrap(),
+    ));
+
+    assert!(brc20_index.process_transfer_send(
+      "ORDI",
+      &owner(2),
+      &owner(2),
+      inscription_id(3),
+      brc20_tx(4, &owner(3), 400),
+      sat_point(4),
+    ));
+
+    assert!(!brc20_index.process_transfer_send(
+      "ordi",
+      &owner(2),
+      &owner(2),
+      inscription_id(3),
+      brc20_tx(5, &owner(3), 500),
+      sat_point(5),
+    ));
+
+    assert_eq!(brc20_index.get_invalid_tx_map().len(), 0);
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+
+    let sender = ticker.get_user_balance(&owner(2)).unwrap();
+    assert_eq!(sender.get_overall_balance(), base_units("60", 18));
+    assert_eq!(sender.get_available_balance(), base_units("60", 18));
+    assert!(sender.get_active_transfer_inscriptions().is_empty());
+    assert_eq!(sender.get_transfer_sends().len(), 1);
+    assert!(sender.get_transfer_receives().is_empty());
+
+    let receiver = ticker.get_user_balance(&owner(3)).unwrap();
+    assert_eq!(receiver.get_overall_balance(), base_units("40", 18));
+    assert!(receiver.get_transfer_sends().is_empty());
+    assert_eq!(receiver.get_transfer_receives().len(), 1);
+    assert_eq!(
+      receiver.get_transfer_receives()[0]
+        .get_send_tx()
+        .unwrap()
+        .get_txid(),
+      txid(4)
+    );
+    assert_eq!(
+      receiver.get_transfer_receives()[0].get_sat_point(),
+      sat_point(4)
+    );
+  }
+
+  #[test]
+  fn skipped_inscriptions_are_only_kept_when_recorded() {
+    let mut brc20_index = Brc20Index::new();
+    brc20_index.add_skipped(inscription_id(1), "Unsupported content type".into());
+    assert_eq!(brc20_index.get_skipped_count(), 1);
+    assert_eq!(brc20_index.get_skipped(), None);
+
+    brc20_index.record_skipped();
+    brc20_index.add_skipped(inscription_id(2), "Not a BRC-20 operation".into());
+    assert_eq!(brc20_index.get_skipped_count(), 2);
+    assert_eq!(
+      brc20_index.get_skipped(),
+      Some([(inscription_id(2), "Not a BRC-20 operation".to_string())].as_slice())
+    );
+  }
+
+  #[test]
+  fn reinscription_is_ignored() {
+    let mut brc20_index = Brc20Index::new();
+
+    assert!(brc20_index.claim_sat(
+      inscription_id(1),
+      "mint",
+      "ordi",
+      brc20_tx(1, &owner(1), 100),
+      sat_point(1)
+    ));
+    assert!(brc20_index.claim_sat(
+      inscription_id(2),
+      "mint",
+      "ordi",
+      brc20_tx(2, &owner(1), 100),
+      sat_point(2)
+    ));
+    assert!(!brc20_index.claim_sat(
+      inscription_id(3),
+      "transfer",
+      "ORDI",
+      brc20_tx(3, &owner(1), 200),
+      sat_point(1)
+    ));
+
+    assert_eq!(brc20_index.get_invalid_tx_map().len(), 1);
+
+    let invalid_tx = brc20_index.get_invalid_tx_map().iter().next().unwrap();
+    assert_eq!(invalid_tx.get_inscription_id(), inscription_id(3));
+    assert_eq!(invalid_tx.get_op(), "transfer");
+    assert_eq!(invalid_tx.get_tick(), "ORDI");
+    assert_eq!(invalid_tx.get_reason(), "Reinscription ignored");
+  }
+
+  #[test]
+  fn reinscription_is_logged() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("brc20.log");
+
+    let mut brc20_index = Brc20Index::with_validation_log(ValidationLog::new(path.clone()));
+
+    for n in [1, 2] {
+      brc20_index.claim_sat(
+        inscription_id(n),
+        "mint",
+        "ordi",
+        brc20_tx(n.into(), &owner(1), 100),
+        sat_point(1),
+      );
+    }
+
+    let entries = fs::read_to_string(&path)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect::<Vec<serde_json::Value>>();
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["txid"], txid(2).to_string());
+    assert_eq!(entries[0]["valid"], false);
+    assert_eq!(entries[0]["reason"], "Reinscription ignored");
+  }
+
+  #[test]
+  fn competing_final_mints_never_go_negative() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+    mint(&mut brc20_index, 2, &owner(2), 200, "999.7");
+
+    assert!(brc20_index.process_mint(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(3), 300),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"0.5"}"#).unwrap(),
+    ));
+    assert!(!brc20_index.process_mint(
+      inscription_id(4),
+      4,
+      brc20_tx(4, &owner(4), 300),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"0.5"}"#).unwrap(),
+    ));
+
+    assert_eq!(rejection(&brc20_index, 4), "Ticker fully minted");
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert!(ticker.is_fully_minted());
+    assert_eq!(ticker.get_total_minted(), ticker.get_max_supply());
+    assert!(ticker
+      .get_mints()
+      .iter()
+      .all(|mint_tx| mint_tx.get_amount() > 0));
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(3))
+        .unwrap()
+        .get_overall_balance(),
+      300_000_000_000_000_000
+    );
+    assert_eq!(ticker.get_user_balance(&owner(4)), None);
+  }
+
+  #[test]
+  fn first_mint_applied_wins_final_supply() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    for (n, amt) in [(2, "900"), (3, "100"), (4, "100")] {
+      brc20_index.process_mint(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(n.try_into().unwrap()), 200),
+        mint_script(&format!(
+          r#"{{"p":"brc-20","op":"mint","tick":"ordi","amt":"{amt}"}}"#
+        ))
+        .unwrap(),
+      );
+    }
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert!(ticker.is_fully_minted());
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(3))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("100", 18)
+    );
+    assert!(ticker.get_user_balance(&owner(4)).is_none());
+    assert_eq!(rejection(&brc20_index, 4), "Ticker fully minted");
+  }
+
+  #[test]
+  fn active_transfers_across_holders() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    for n in [2, 3] {
+      assert!(brc20_index.process_mint(
+        inscription_id(n.into()),
+        n.into(),
+        brc20_tx(n.into(), &owner(n), 200),
+        mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+      ));
+    }
+
+    for (n, owner_n, blocktime) in [(4, 3, 300), (5, 2, 400), (6, 2, 500)] {
+      assert!(brc20_index.process_transfer(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(owner_n), blocktime),
+        transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"10"}"#).unwrap(),
+      ));
+    }
+
+    assert!(brc20_index.process_transfer_send(
+      "ordi",
+      &owner(2),
+      &owner(2),
+      inscription_id(6),
+      brc20_tx(7, &owner(4), 600),
+      sat_point(7),
+    ));
+
+    assert_eq!(
+      brc20_index
+        .get_ticker("ordi")
+        .unwrap()
+        .get_all_active_transfers()
+        .into_iter()
+        .map(|transfer_tx| (
+          transfer_tx.get_inscription_id(),
+          transfer_tx.get_brc20_tx().get_owner().clone()
+        ))
+        .collect::<Vec<(InscriptionId, Owner)>>(),
+      [(inscription_id(4), owner(3)), (inscription_id(5), owner(2))]
+    );
+  }
+
+  #[test]
+  fn transfer_spent_by_non_owner_is_invalid() {
+    let mut brc20_index = Brc20Index::new();
+    assert!(deploy(&mut brc20_index, 1, "ordi"));
+
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+
+    assert!(brc20_index.process_transfer(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"40"}"#).unwrap(),
+    ));
+
+    assert!(!brc20_index.process_transfer_send(
+      "ordi",
+      &owner(2),
+      &owner(4),
+      inscription_id(3),
+      brc20_tx(4, &owner(4), 400),
+      sat_point(4),
+    ));
+
+    assert_eq!(rejection(&brc20_index, 3), "Transfer spent by non-owner");
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert!(ticker.get_user_balance(&owner(4)).is_none());
+
+    let sender = ticker.get_user_balance(&owner(2)).unwrap();
+    assert_eq!(sender.get_overall_balance(), base_units("100", 18));
+    assert_eq!(sender.get_active_transfer_inscriptions().len(), 1);
+  }
+}
@@ -0,0 +1,336 @@
+use {super::*, bitcoincore_rpc::bitcoincore_rpc_json::ScriptPubkeyType};
+
+/// Broad kind of script an owner's output pays to, for classifying
+/// holders. `Unknown` if the node didn't report the script's type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum AddressType {
+  Legacy,
+  Segwit,
+  Taproot,
+  Other,
+  Unknown,
+}
+
+impl From<Option<ScriptPubkeyType>> for AddressType {
+  fn from(script_pubkey_type: Option<ScriptPubkeyType>) -> Self {
+    match script_pubkey_type {
+      Some(
+        ScriptPubkeyType::Pubkey
+        | ScriptPubkeyType::PubkeyHash
+        | ScriptPubkeyType::ScriptHash
+        | ScriptPubkeyType::MultiSig,
+      ) => Self::Legacy,
+      Some(ScriptPubkeyType::Witness_v0_KeyHash | ScriptPubkeyType::Witness_v0_ScriptHash) => {
+        Self::Segwit
+      }
+      Some(ScriptPubkeyType::Witness_v1_Taproot) => Self::Taproot,
+      Some(
+        ScriptPubkeyType::Nonstandard
+        | ScriptPubkeyType::NullData
+        | ScriptPubkeyType::Witness_Unknown,
+      ) => Self::Other,
+      None => Self::Unknown,
+    }
+  }
+}
+
+impl Display for AddressType {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Legacy => write!(f, "legacy"),
+      Self::Segwit => write!(f, "segwit"),
+      Self::Taproot => write!(f, "taproot"),
+      Self::Other => write!(f, "other"),
+      Self::Unknown => write!(f, "unknown"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Brc20Tx {
+  txid: Txid,
+  vout: u32,
+  blocktime: u64,
+  height: u64,
+  owner: Owner,
+  script_pubkey_type: Option<ScriptPubkeyType>,
+}
+
+impl Brc20Tx {
+  pub(crate) fn new(
+    raw_tx_result: &GetRawTransactionResult,
+    network: Network,
+  ) -> Result<Self, Brc20Error> {
+    let vout = raw_tx_result.vout.first().ok_or(Brc20Error::NoOutputs)?.n;
+
+    Self::for_output(raw_tx_result, vout, network)
+  }
+
+  pub(crate) fn for_output(
+    raw_tx_result: &GetRawTransactionResult,
+    vout: u32,
+    network: Network,
+  ) -> Result<Self, Brc20Error> {
+    let blocktime = raw_tx_result
+      .blocktime
+      .ok_or(Brc20Error::BlocktimeNotFound)?;
+
+    let owner = get_owner_of_output(
+      &OutPoint {
+        txid: raw_tx_result.txid,
+        vout,
+      },
+      raw_tx_result,
+      network,
+    )?;
+
+    let script_pubkey_type = raw_tx_result
+      .vout
+      .get(usize::try_from(vout).unwrap())
+      .and_then(|output| output.script_pub_key.type_);
+
+    Ok(Self {
+      txid: raw_tx_result.txid,
+      vout,
+      blocktime: u64::try_from(blocktime).unwrap(),
+      height: 0,
+      owner,
+      script_pubkey_type,
+    })
+  }
+
+  pub(crate) fn get_txid(&self) -> Txid {
+    self.txid
+  }
+
+  pub(crate) fn get_vout(&self) -> u32 {
+    self.vout
+  }
+
+  pub(crate) fn get_blocktime(&self) -> u64 {
+    self.blocktime
+  }
+
+  /// Sets the height of the block the transaction was mined in, which the
+  /// node doesn't report alongside the transaction.
+  pub(crate) fn with_height(self, height: u64) -> Self {
+    Self { height, ..self }
+  }
+
+  pub(crate) fn get_height(&self) -> u64 {
+    self.height
+  }
+
+  pub(crate) fn get_owner(&self) -> &Owner {
+    &self.owner
+  }
+
+  /// The type of the owner's output script, as reported by the node.
+  pub(crate) fn get_script_pubkey_type(&self) -> Option<ScriptPubkeyType> {
+    self.script_pubkey_type
+  }
+
+  pub(crate) fn get_address_type(&self) -> AddressType {
+    self.get_script_pubkey_type().into()
+  }
+}
+
+pub(crate) fn get_owner_of_output(
+  outpoint: &OutPoint,
+  raw_tx_info: &GetRawTransactionResult,
+  network: Network,
+) -> Result<Owner, Brc20Error> {
+  let output = usize::try_from(outpoint.vout)
+    .ok()
+    .and_then(|vout| raw_tx_info.vout.get(vout))
+    .ok_or(Brc20Error::OutputIndexOutOfRange)?;
+
+  Ok(Owner::from_script(
+    &Script::from(output.script_pub_key.hex.clone()),
+    network,
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn new() {
+    let brc20_tx = Brc20Tx::new(&raw_tx_result(1, &owner(1), 100), Network::Bitcoin).unwrap();
+    assert_eq!(brc20_tx.get_txid(), txid(1));
+    assert_eq!(brc20_tx.get_vout(), 0);
+    assert_eq!(brc20_tx.get_blocktime(), 100);
+    assert_eq!(brc20_tx.get_owner(), &owner(1));
+  }
+
+  #[test]
+  fn new_without_outputs_is_an_error() {
+    let mut raw_tx_result = raw_tx_result(1, &owner(1), 100);
+    raw_tx_result.vout.clear();
+
+    assert_eq!(
+      Brc20Tx::new(&raw_tx_result, Network::Bitcoin),
+      Err(Brc20Error::NoOutputs)
+    );
+    assert_eq!(
+      Brc20Error::NoOutputs.to_string(),
+      "Transaction has no outputs"
+    );
+  }
+
+  #[test]
+  fn owner_of_second_output() {
+    let mut raw_tx_result = raw_tx_result(1, &owner(1), 100);
+    let mut output = raw_tx_result.vout[0].clone();
+    output.n = 1;
+    output.script_pub_key.hex = script_pubkey(&owner(2)).to_bytes();
+    raw_tx_result.vout.push(output);
+
+    assert_eq!(
+      get_owner_of_output(
+        &OutPoint {
+          txid: txid(1),
+          vout: 1
+        },
+        &raw_tx_result,
+        Network::Bitcoin
+      ),
+      Ok(owner(2))
+    );
+  }
+
+  #[test]
+  fn owner_of_output_past_last_output_is_an_error() {
+    let mut raw_tx_result = raw_tx_result(1, &owner(1), 100);
+    let mut output = raw_tx_result.vout[0].clone();
+    output.n = 1;
+    raw_tx_result.vout.push(output);
+
+    assert_eq!(
+      get_owner_of_output(
+        &OutPoint {
+          txid: txid(1),
+          vout: 99
+        },
+        &raw_tx_result,
+        Network::Bitcoin
+      ),
+      Err(Brc20Error::OutputIndexOutOfRange)
+    );
+    assert_eq!(
+      Brc20Error::OutputIndexOutOfRange.to_string(),
+      "Output index out of range"
+    );
+  }
+
+  /// `getrawtransaction` output, as returned by Bitcoin Core, paying to a
+  /// P2PKH, a P2WPKH, and a P2TR output.
+  const FIXTURE: &str = r#"{
+    "in_active_chain": true,
+    "hex": "",
+    "txid": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+    "hash": "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+    "size": 0,
+    "vsize": 0,
+    "version": 2,
+    "locktime": 0,
+    "vin": [],
+    "vout": [
+      {
+        "value": 0.0001,
+        "n": 0,
+        "scriptPubKey": {
+          "asm": "OP_DUP OP_HASH160 62e907b15cbf27d5425399ebf6f0fb50ebb88f18 OP_EQUALVERIFY OP_CHECKSIG",
+          "hex": "76a91462e907b15cbf27d5425399ebf6f0fb50ebb88f1888ac",
+          "type": "pubkeyhash"
+        }
+      },
+      {
+        "value": 0.0001,
+        "n": 1,
+        "scriptPubKey": {
+          "asm": "0 751e76e8199196d454941c45d1b3a323f1433bd6",
+          "hex": "0014751e76e8199196d454941c45d1b3a323f1433bd6",
+          "type": "witness_v0_keyhash"
+        }
+      },
+      {
+        "value": 0.0001,
+        "n": 2,
+        "scriptPubKey": {
+          "asm": "1 79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+          "hex": "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+          "type": "witness_v1_taproot"
+        }
+      }
+    ],
+    "confirmations": 1,
+    "time": 100,
+    "blocktime": 100
+  }"#;
+
+  fn fixture_owner(vout: u32, network: Network) -> Owner {
+    let raw_tx_result = serde_json::from_str::<GetRawTransactionResult>(FIXTURE).unwrap();
+
+    get_owner_of_output(
+      &OutPoint {
+        txid: raw_tx_result.txid,
+        vout,
+      },
+      &raw_tx_result,
+      network,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn owners_of_fixture_outputs() {
+    assert_eq!(
+      fixture_owner(0, Network::Bitcoin).to_string(),
+      "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"
+    );
+    assert_eq!(
+      fixture_owner(1, Network::Bitcoin).to_string(),
+      "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+    );
+    assert_eq!(
+      fixture_owner(2, Network::Bitcoin).to_string(),
+      "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+    );
+  }
+
+  #[test]
+  fn fixture_owners_are_classified_by_address_type() {
+    let raw_tx_result = serde_json::from_str::<GetRawTransactionResult>(FIXTURE).unwrap();
+
+    let address_type = |vout| {
+      Brc20Tx::for_output(&raw_tx_result, vout, Network::Bitcoin)
+        .unwrap()
+        .get_address_type()
+    };
+
+    assert_eq!(address_type(0), AddressType::Legacy);
+    assert_eq!(address_type(1), AddressType::Segwit);
+    assert_eq!(address_type(2), AddressType::Taproot);
+    assert_eq!(
+      Brc20Tx::for_output(&raw_tx_result, 2, Network::Bitcoin)
+        .unwrap()
+        .get_script_pubkey_type(),
+      Some(ScriptPubkeyType::Witness_v1_Taproot)
+    );
+    assert_eq!(
+      brc20_tx(1, &owner(1), 100).get_address_type(),
+      AddressType::Unknown
+    );
+  }
+
+  #[test]
+  fn owners_of_fixture_outputs_follow_network() {
+    assert_eq!(
+      fixture_owner(1, Network::Testnet).to_string(),
+      "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+    );
+  }
+}
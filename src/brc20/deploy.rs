@@ -0,0 +1,624 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Brc20Deploy {
+  pub(crate) p: String,
+  pub(crate) op: String,
+  pub(crate) tick: String,
+  pub(crate) max: String,
+  pub(crate) lim: Option<String>,
+  pub(crate) dec: Option<String>,
+  pub(crate) self_mint: Option<String>,
+  /// The inscription body the script was parsed from, byte for byte.
+  #[serde(skip_deserializing)]
+  pub(crate) raw: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Brc20DeployTx {
+  inscription_id: InscriptionId,
+  inscription_number: i64,
+  brc20_tx: Brc20Tx,
+  deploy_script: Brc20Deploy,
+  #[serde(serialize_with = "serialize_base_units")]
+  max_supply: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  limit: u128,
+  decimals: u8,
+}
+
+impl Brc20DeployTx {
+  /// Decimals are `default_decimals`, usually `DEFAULT_DECIMALS`, until
+  /// validation reads an explicit `dec`, which may be zero for integer-only
+  /// tickers.
+  pub(crate) fn new(
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    deploy_script: Brc20Deploy,
+    default_decimals: u8,
+  ) -> Self {
+    Self {
+      inscription_id,
+      inscription_number,
+      brc20_tx,
+      deploy_script,
+      max_supply: 0,
+      limit: 0,
+      decimals: default_decimals,
+    }
+  }
+
+  pub(crate) fn validate_deploy_script(
+    mut self,
+    tickers: &HashMap<String, Brc20Ticker>,
+    ticker_length_policy: &TickerLengthPolicy,
+  ) -> Result<Self, Brc20Error> {
+    if !ticker_length_policy.allows(&self.deploy_script.tick, self.is_self_mint()) {
+      return Err(Brc20Error::InvalidTickerLength(self.deploy_script.tick));
+    }
+
+    if let Some(ticker) = tickers.get(&self.get_ticker()) {
+      return Err(Brc20Error::TickerAlreadyExists {
+        tick: self.deploy_script.tick,
+        existing: ticker.get_deploy_tx().deploy_script.tick.clone(),
+      });
+    }
+
+    if let Some(decimals) = &self.deploy_script.dec {
+      self.decimals = decimals
+        .parse::<u8>()
+        .ok()
+        .filter(|decimals| *decimals <= MAX_DECIMALS)
+        .ok_or(Brc20Error::InvalidDecimals)?;
+    }
+
+    // A max supply that can't be expressed in whole base units is malformed.
+    self.max_supply =
+      convert_to_base_units(&self.deploy_script.max, self.decimals).map_err(|err| match err {
+        Brc20Error::TooManyDecimals { .. } => Brc20Error::MaxSupplyTooManyDecimals,
+        Brc20Error::NumberTooLarge(_) => Brc20Error::InvalidMaxSupply,
+        err => err,
+      })?;
+
+    if self.max_supply == 0
+      || self.max_supply > u128::from(MAX_SUPPLY) * 10u128.pow(self.decimals.into())
+    {
+      return Err(Brc20Error::InvalidMaxSupply);
+    }
+
+    self.limit = match &self.deploy_script.lim {
+      Some(limit) => convert_to_base_units(limit, self.decimals).map_err(|err| match err {
+        Brc20Error::NumberTooLarge(_) => Brc20Error::InvalidLimit,
+        err => err,
+      })?,
+      None => self.max_supply,
+    };
+
+    if self.limit == 0 || self.limit > self.max_supply {
+      return Err(Brc20Error::InvalidLimit);
+    }
+
+    Ok(self)
+  }
+
+  pub(crate) fn get_inscription_id(&self) -> InscriptionId {
+    self.inscription_id
+  }
+
+  pub(crate) fn get_inscription_number(&self) -> i64 {
+    self.inscription_number
+  }
+
+  pub(crate) fn get_brc20_tx(&self) -> &Brc20Tx {
+    &self.brc20_tx
+  }
+
+  pub(crate) fn get_ticker(&self) -> String {
+    self.deploy_script.tick.to_lowercase()
+  }
+
+  pub(crate) fn get_deploy_script(&self) -> &Brc20Deploy {
+    &self.deploy_script
+  }
+
+  /// The ticker as deployed, with its original casing.
+  pub(crate) fn get_display_ticker(&self) -> &str {
+    &self.deploy_script.tick
+  }
+
+  /// The original deploy JSON, for verifying a deploy against its
+  /// inscription, which the parsed script can't reproduce exactly.
+  pub(crate) fn get_raw_body(&self) -> &str {
+    &self.deploy_script.raw
+  }
+
+  pub(crate) fn is_self_mint(&self) -> bool {
+    self.deploy_script.self_mint.as_deref() == Some("true")
+  }
+
+  /// The exact max supply in base units.
+  pub(crate) fn get_max_supply(&self) -> u128 {
+    self.max_supply
+  }
+
+  /// The exact limit in base units.
+  pub(crate) fn get_limit(&self) -> u128 {
+    self.limit
+  }
+
+  pub(crate) fn get_decimals(&self) -> u8 {
+    self.decimals
+  }
+}
+
+impl Display for Brc20DeployTx {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "Deploy of {} with max supply {}, limit {}, and {} decimals by {} in {}",
+      self.deploy_script.tick,
+      format_base_units(self.max_supply, self.decimals),
+      format_base_units(self.limit, self.decimals),
+      self.decimals,
+      self.brc20_tx.get_owner(),
+      self.brc20_tx.get_txid(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_deploy() {
+    assert_eq!(
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#),
+      Some(Brc20Deploy {
+        p: "brc-20".into(),
+        op: "deploy".into(),
+        tick: "ordi".into(),
+        max: "21000000".into(),
+        lim: None,
+        dec: None,
+        self_mint: None,
+        raw: r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#.into(),
+      })
+    );
+  }
+
+  #[test]
+  fn parse_deploy_with_optional_fields() {
+    assert_eq!(
+      deploy_script(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"8"}"#
+      ),
+      Some(Brc20Deploy {
+        p: "brc-20".into(),
+        op: "deploy".into(),
+        tick: "ordi".into(),
+        max: "21000000".into(),
+        lim: Some("1000".into()),
+        dec: Some("8".into()),
+        self_mint: None,
+        raw:
+          r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"8"}"#
+            .into(),
+      })
+    );
+  }
+
+  #[test]
+  fn raw_body_is_stored_verbatim() {
+    let body =
+      "{ \"tick\": \"ordi\",\n  \"p\": \"brc-20\", \"op\": \"deploy\", \"max\": \"21000000\" }";
+
+    let deploy_tx = validate(body).unwrap();
+    assert_eq!(deploy_tx.get_raw_body(), body);
+
+    let document = serde_json::to_value(&deploy_tx).unwrap();
+    assert_eq!(document["deploy_script"]["raw"], body);
+  }
+
+  #[test]
+  fn deploy_with_unknown_field_is_rejected() {
+    assert_eq!(
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","foo":"bar"}"#),
+      None
+    );
+  }
+
+  #[test]
+  fn deploy_with_wrong_op_is_rejected() {
+    assert_eq!(
+      deploy_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","max":"21000000"}"#),
+      None
+    );
+  }
+
+  fn validate(body: &str) -> Result<Brc20DeployTx, Brc20Error> {
+    Brc20DeployTx::new(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(body).unwrap(),
+      DEFAULT_DECIMALS,
+    )
+    .validate_deploy_script(&HashMap::new(), &TickerLengthPolicy::default())
+  }
+
+  #[test]
+  fn valid_deploy() {
+    let deploy_tx = validate(
+      r#"{"p":"brc-20","op":"deploy","tick":"ORDI","max":"21000000","lim":"1000","dec":"8"}"#,
+    )
+    .unwrap();
+    assert_eq!(deploy_tx.get_ticker(), "ordi");
+    assert_eq!(deploy_tx.get_max_supply(), base_units("21000000", 8));
+    assert_eq!(deploy_tx.get_limit(), base_units("1000", 8));
+    assert_eq!(deploy_tx.get_decimals(), 8);
+  }
+
+  #[test]
+  fn deploy_defaults() {
+    let deploy_tx =
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#).unwrap();
+    assert_eq!(deploy_tx.get_limit(), base_units("21000000", 18));
+    assert_eq!(deploy_tx.get_decimals(), 18);
+  }
+
+  #[test]
+  fn deploy_with_limit_equal_to_max_supply() {
+    let deploy_tx =
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"21000000"}"#)
+        .unwrap();
+    assert_eq!(deploy_tx.get_limit(), deploy_tx.get_max_supply());
+    assert_eq!(format_base_units(deploy_tx.get_limit(), 18), "21000000.0");
+
+    let ticker = Brc20Ticker::new(deploy_tx);
+    assert_eq!(ticker.get_limit(), ticker.get_max_supply());
+  }
+
+  #[test]
+  fn deploy_ticker_must_be_four_bytes() {
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ord","max":"21000000"}"#),
+      Err(Brc20Error::InvalidTickerLength("ord".into()))
+    );
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordin","max":"21000000"}"#),
+      Err(Brc20Error::InvalidTickerLength("ordin".into()))
+    );
+  }
+
+  #[test]
+  fn self_mint_deploy_allows_five_byte_ticker() {
+    let deploy_tx = validate(
+      r#"{"p":"brc-20","op":"deploy","tick":"ordix","max":"21000000","self_mint":"true"}"#,
+    )
+    .unwrap();
+    assert_eq!(deploy_tx.get_ticker(), "ordix");
+    assert!(deploy_tx.is_self_mint());
+
+    assert_eq!(
+      validate(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordix","max":"21000000","self_mint":"false"}"#
+      ),
+      Err(Brc20Error::InvalidTickerLength("ordix".into()))
+    );
+    assert_eq!(
+      validate(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordixy","max":"21000000","self_mint":"true"}"#
+      ),
+      Err(Brc20Error::InvalidTickerLength("ordixy".into()))
+    );
+  }
+
+  #[test]
+  fn ticker_length_policy_can_allow_other_lengths() {
+    let validate = |body: &str, ticker_length_policy: &TickerLengthPolicy| {
+      Brc20DeployTx::new(
+        inscription_id(1),
+        1,
+        brc20_tx(1, &owner(1), 100),
+        deploy_script(body).unwrap(),
+        DEFAULT_DECIMALS,
+      )
+      .validate_deploy_script(&HashMap::new(), ticker_length_policy)
+    };
+
+    let standard = r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#;
+    let self_mint =
+      r#"{"p":"brc-20","op":"deploy","tick":"ordix","max":"21000000","self_mint":"true"}"#;
+    let six_bytes = r#"{"p":"brc-20","op":"deploy","tick":"ordixy","max":"21000000"}"#;
+
+    let default = TickerLengthPolicy::default();
+    assert!(validate(standard, &default).is_ok());
+    assert!(validate(self_mint, &default).is_ok());
+
+    let err = validate(six_bytes, &default).unwrap_err();
+    assert_eq!(err, Brc20Error::InvalidTickerLength("ordixy".into()));
+    assert_eq!(err.to_string(), "Invalid ticker length: ordixy");
+
+    let experimental = TickerLengthPolicy::new([4, 6], [5], TickerLengthMode::Bytes);
+    assert!(validate(standard, &experimental).is_ok());
+    assert!(validate(self_mint, &experimental).is_ok());
+    assert_eq!(
+      validate(six_bytes, &experimental).unwrap().get_ticker(),
+      "ordixy"
+    );
+    assert_eq!(
+      validate(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordix","max":"21000000"}"#,
+        &experimental
+      ),
+      Err(Brc20Error::InvalidTickerLength("ordix".into()))
+    );
+  }
+
+  #[test]
+  fn ticker_length_mode_counts_bytes_or_chars() {
+    let validate = |tick: &str, mode: TickerLengthMode| {
+      Brc20DeployTx::new(
+        inscription_id(1),
+        1,
+        brc20_tx(1, &owner(1), 100),
+        deploy_script(&format!(
+          r#"{{"p":"brc-20","op":"deploy","tick":"{tick}","max":"21000000"}}"#
+        ))
+        .unwrap(),
+        DEFAULT_DECIMALS,
+      )
+      .validate_deploy_script(&HashMap::new(), &TickerLengthPolicy::new([4], [5], mode))
+    };
+
+    // Four characters, five bytes.
+    assert_eq!(
+      validate("ordé", TickerLengthMode::Bytes),
+      Err(Brc20Error::InvalidTickerLength("ordé".into()))
+    );
+    assert!(validate("ordé", TickerLengthMode::Chars).is_ok());
+
+    // One character, four bytes.
+    assert!(validate("😀", TickerLengthMode::Bytes).is_ok());
+    assert_eq!(
+      validate("😀", TickerLengthMode::Chars),
+      Err(Brc20Error::InvalidTickerLength("😀".into()))
+    );
+
+    assert!(validate("ordi", TickerLengthMode::Bytes).is_ok());
+    assert!(validate("ordi", TickerLengthMode::Chars).is_ok());
+  }
+
+  #[test]
+  fn deploy_of_existing_ticker_is_invalid() {
+    let deploy_tx =
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#).unwrap();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+
+    assert_eq!(
+      Brc20DeployTx::new(
+        inscription_id(2),
+        2,
+        brc20_tx(2, &owner(2), 200),
+        deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1"}"#).unwrap(),
+        DEFAULT_DECIMALS,
+      )
+      .validate_deploy_script(&tickers, &TickerLengthPolicy::default()),
+      Err(Brc20Error::TickerAlreadyExists {
+        tick: "ordi".into(),
+        existing: "ordi".into(),
+      })
+    );
+  }
+
+  #[test]
+  fn deploy_of_existing_ticker_with_different_case_is_invalid() {
+    let deploy_tx =
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#).unwrap();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+
+    let err = Brc20DeployTx::new(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ORDI","max":"1"}"#).unwrap(),
+      DEFAULT_DECIMALS,
+    )
+    .validate_deploy_script(&tickers, &TickerLengthPolicy::default())
+    .unwrap_err();
+
+    assert_eq!(
+      err,
+      Brc20Error::TickerAlreadyExists {
+        tick: "ORDI".into(),
+        existing: "ordi".into(),
+      }
+    );
+    assert_eq!(
+      err.to_string(),
+      "Ticker already exists: ORDI matches ordi, tickers are case-insensitive"
+    );
+  }
+
+  #[test]
+  fn deploy_with_invalid_decimals_is_invalid() {
+    for dec in ["abc", "999", "19.5", "19", "-1", ""] {
+      assert_eq!(
+        validate(&format!(
+          r#"{{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","dec":"{dec}"}}"#
+        )),
+        Err(Brc20Error::InvalidDecimals),
+        "{dec}"
+      );
+    }
+
+    assert_eq!(Brc20Error::InvalidDecimals.to_string(), "Invalid decimals");
+  }
+
+  #[test]
+  fn deploy_with_zero_decimals_only_allows_integer_amounts() {
+    let deploy_tx = validate(
+      r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+    )
+    .unwrap();
+    assert_eq!(deploy_tx.get_decimals(), 0);
+
+    let mut tickers = HashMap::new();
+    tickers.insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+
+    let mint = |amt: &str| {
+      Brc20MintTx::new(
+        inscription_id(2),
+        2,
+        brc20_tx(2, &owner(2), 200),
+        mint_script(&format!(
+          r#"{{"p":"brc-20","op":"mint","tick":"ordi","amt":"{amt}"}}"#
+        ))
+        .unwrap(),
+      )
+      .validate(&tickers)
+    };
+
+    assert_eq!(mint("10").unwrap().get_amount(), base_units("10", 0));
+    assert_eq!(
+      mint("1.5"),
+      Err(Brc20Error::TooManyDecimals { decimals: 0 })
+    );
+
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1.5","dec":"0"}"#),
+      Err(Brc20Error::MaxSupplyTooManyDecimals)
+    );
+  }
+
+  #[test]
+  fn deploy_with_max_finer_than_decimals_is_invalid() {
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1.0000000001","dec":"8"}"#),
+      Err(Brc20Error::MaxSupplyTooManyDecimals)
+    );
+    assert_eq!(
+      Brc20Error::MaxSupplyTooManyDecimals.to_string(),
+      "Max supply has too many decimals"
+    );
+
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1.00000001","dec":"8"}"#)
+        .unwrap()
+        .get_max_supply(),
+      base_units("1.00000001", 8)
+    );
+  }
+
+  #[test]
+  fn deploy_with_numeric_decimals_is_valid() {
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","dec":"8"}"#)
+        .unwrap()
+        .get_decimals(),
+      8
+    );
+  }
+
+  #[test]
+  fn deploy_with_empty_max_is_invalid() {
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":""}"#),
+      Err(Brc20Error::MissingNumber)
+    );
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":""}"#),
+      Err(Brc20Error::MissingNumber)
+    );
+  }
+
+  #[test]
+  fn deploy_with_invalid_max_supply_is_invalid() {
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"0"}"#),
+      Err(Brc20Error::InvalidMaxSupply)
+    );
+  }
+
+  #[test]
+  fn deploy_with_max_supply_beyond_base_units_is_invalid() {
+    assert_eq!(
+      validate(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000000000000000000000000000000000000000"}"#
+      ),
+      Err(Brc20Error::InvalidMaxSupply)
+    );
+  }
+
+  #[test]
+  fn deploy_of_sats_scale_max_supply_is_exact() {
+    let deploy_tx = validate(
+      r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"21000000000000","lim":"100000000","dec":"18"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      deploy_tx.get_max_supply(),
+      21_000_000_000_000 * 10u128.pow(18)
+    );
+    assert_eq!(deploy_tx.get_limit(), 100_000_000 * 10u128.pow(18));
+    assert_eq!(
+      format_base_units(deploy_tx.get_max_supply(), 18),
+      "21000000000000.0"
+    );
+
+    let deploy_tx = validate(
+      r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"20999999999999.999999999999999999"}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      format_base_units(deploy_tx.get_max_supply(), 18),
+      "20999999999999.999999999999999999"
+    );
+    assert_eq!(
+      Brc20Ticker::new(deploy_tx.clone()).get_max_supply(),
+      deploy_tx.get_max_supply()
+    );
+  }
+
+  #[test]
+  fn deploy_with_limit_above_max_supply_is_invalid() {
+    assert_eq!(
+      validate(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"100","lim":"1000"}"#),
+      Err(Brc20Error::InvalidLimit)
+    );
+  }
+
+  #[test]
+  fn display_formats_amounts_with_decimals() {
+    let deploy_tx = Brc20DeployTx {
+      inscription_id: inscription_id(1),
+      inscription_number: 1,
+      brc20_tx: brc20_tx(1, &owner(1), 100),
+      deploy_script: deploy_script(
+        r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"8"}"#,
+      )
+      .unwrap(),
+      max_supply: 21_000_000 * 100_000_000,
+      limit: 1000 * 100_000_000,
+      decimals: 8,
+    };
+
+    assert_eq!(
+      deploy_tx.to_string(),
+      format!(
+        "Deploy of ordi with max supply 21000000.0, limit 1000.0, and 8 decimals by {} in {}",
+        owner(1),
+        txid(1)
+      )
+    );
+  }
+}
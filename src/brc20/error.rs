@@ -0,0 +1,102 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Brc20Error {
+  BlocktimeNotFound,
+  InsufficientBalance {
+    tick: String,
+    amount: String,
+    available: String,
+  },
+  InvalidAmount,
+  InvalidDecimals,
+  InvalidLimit,
+  InvalidMaxSupply,
+  InvalidNumber(String),
+  InvalidProtocol(String),
+  InvalidTickerLength(String),
+  MalformedNumber {
+    number: String,
+    reason: &'static str,
+  },
+  MaxSupplyTooManyDecimals,
+  MintAmountExceedsLimit,
+  MintPrecedesDeploy,
+  MissingNumber,
+  NoOutputs,
+  NumberTooLarge(String),
+  OutputIndexOutOfRange,
+  ReinscriptionIgnored,
+  RepeatedMintInArray(String),
+  TickerAlreadyExists {
+    tick: String,
+    existing: String,
+  },
+  TickerFullyMinted,
+  TickerNotFound(String),
+  TooManyDecimals {
+    decimals: u8,
+  },
+  TransferInArray,
+  TransferSpentByNonOwner,
+  UnauthorizedSelfMint(String),
+  UserBalanceNotFound,
+}
+
+impl Display for Brc20Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::BlocktimeNotFound => write!(f, "Blocktime not found"),
+      Self::InsufficientBalance {
+        tick,
+        amount,
+        available,
+      } => write!(
+        f,
+        "Transfer amount {amount} {tick} exceeds available balance {available} {tick}"
+      ),
+      Self::InvalidAmount => write!(f, "Amount must be greater than zero"),
+      Self::InvalidDecimals => write!(f, "Invalid decimals"),
+      Self::InvalidLimit => write!(f, "Invalid limit"),
+      Self::InvalidMaxSupply => write!(f, "Invalid max supply"),
+      Self::InvalidNumber(number) => write!(f, "Invalid number: {number}"),
+      Self::InvalidProtocol(protocol) => write!(f, "Invalid protocol: {protocol}"),
+      Self::InvalidTickerLength(tick) => write!(f, "Invalid ticker length: {tick}"),
+      Self::MalformedNumber { number, reason } => write!(f, "Malformed number {number}: {reason}"),
+      Self::MaxSupplyTooManyDecimals => write!(f, "Max supply has too many decimals"),
+      Self::MintAmountExceedsLimit => write!(f, "Mint amount exceeds limit"),
+      Self::MintPrecedesDeploy => write!(f, "Mint precedes deploy"),
+      Self::MissingNumber => write!(f, "Missing required numeric field"),
+      Self::NoOutputs => write!(f, "Transaction has no outputs"),
+      Self::NumberTooLarge(number) => write!(f, "Number too large: {number}"),
+      Self::OutputIndexOutOfRange => write!(f, "Output index out of range"),
+      Self::ReinscriptionIgnored => write!(f, "Reinscription ignored"),
+      Self::RepeatedMintInArray(tick) => {
+        write!(f, "Only one mint of {tick} is allowed per inscription")
+      }
+      Self::TickerAlreadyExists { tick, existing } => {
+        if tick == existing {
+          write!(f, "Ticker already exists: {tick}")
+        } else {
+          write!(
+            f,
+            "Ticker already exists: {tick} matches {existing}, tickers are case-insensitive"
+          )
+        }
+      }
+      Self::TickerFullyMinted => write!(f, "Ticker fully minted"),
+      Self::TickerNotFound(tick) => write!(f, "Ticker not found: {tick}"),
+      Self::TooManyDecimals { decimals } => {
+        write!(f, "Number has more than {decimals} decimal places")
+      }
+      Self::TransferInArray => write!(f, "Transfers can't be inscribed in an array"),
+      Self::TransferSpentByNonOwner => write!(f, "Transfer spent by non-owner"),
+      Self::UnauthorizedSelfMint(tick) => {
+        write!(f, "Only the deployer can mint self-mint ticker: {tick}")
+      }
+      Self::UserBalanceNotFound => write!(f, "User balance not found"),
+    }
+  }
+}
+
+impl std::error::Error for Brc20Error {}
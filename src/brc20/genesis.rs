@@ -0,0 +1,200 @@
+use super::*;
+
+/// The size of an inscription's content and what its reveal transaction
+/// paid. Inscriptions revealed together share the fee and virtual size of
+/// their reveal transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub(crate) struct Genesis {
+  pub(crate) size: usize,
+  pub(crate) fee: Option<u64>,
+  pub(crate) vsize: usize,
+  /// Fee rate in sat/vB, if both the fee and the virtual size are known.
+  pub(crate) fee_rate: Option<f64>,
+}
+
+impl Genesis {
+  pub(crate) fn new(
+    size: usize,
+    raw_tx_result: &GetRawTransactionResult,
+    input_values: Option<&[Amount]>,
+  ) -> Self {
+    let fee = input_values.and_then(|input_values| compute_fee(raw_tx_result, input_values));
+    let vsize = raw_tx_result.vsize;
+
+    Self {
+      size,
+      fee,
+      vsize,
+      fee_rate: fee
+        .filter(|_| vsize > 0)
+        .map(|fee| fee as f64 / vsize as f64),
+    }
+  }
+}
+
+/// The fee of `raw_tx_result` given the values of the outputs its inputs
+/// spend, in input order. Returns `None` if `input_values` doesn't cover
+/// every input or the outputs are worth more than the inputs.
+pub(crate) fn compute_fee(
+  raw_tx_result: &GetRawTransactionResult,
+  input_values: &[Amount],
+) -> Option<u64> {
+  if input_values.len() != raw_tx_result.vin.len() {
+    return None;
+  }
+
+  let input_value = input_values.iter().map(|value| value.to_sat()).sum::<u64>();
+
+  let output_value = raw_tx_result
+    .vout
+    .iter()
+    .map(|output| output.value.to_sat())
+    .sum::<u64>();
+
+  let fee = input_value.checked_sub(output_value);
+
+  if fee.is_none() {
+    log::warn!(
+      "outputs of {} are worth more than its inputs, ignoring its fee",
+      raw_tx_result.txid
+    );
+  }
+
+  fee
+}
+
+/// Aggregate size and fee of a set of inscriptions. Inscriptions whose fee
+/// isn't known count towards `inscriptions` and `total_size` only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub(crate) struct FeeStats {
+  pub(crate) inscriptions: usize,
+  pub(crate) total_size: usize,
+  pub(crate) total_fee: u64,
+  /// Total fee over total virtual size, so larger reveals weigh more.
+  pub(crate) average_fee_rate: Option<f64>,
+}
+
+impl FeeStats {
+  pub(crate) fn new<'a>(geneses: impl IntoIterator<Item = &'a Genesis>) -> Self {
+    let mut fee_stats = Self::default();
+    let mut rated_fee = 0;
+    let mut rated_vsize = 0;
+
+    for genesis in geneses {
+      fee_stats.inscriptions += 1;
+      fee_stats.total_size += genesis.size;
+
+      if let Some(fee) = genesis.fee {
+        fee_stats.total_fee += fee;
+
+        if genesis.fee_rate.is_some() {
+          rated_fee += fee;
+          rated_vsize += genesis.vsize;
+        }
+      }
+    }
+
+    fee_stats.average_fee_rate = (rated_vsize > 0).then(|| rated_fee as f64 / rated_vsize as f64);
+
+    fee_stats
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoincore_rpc::bitcoincore_rpc_json::GetRawTransactionResultVin};
+
+  fn input(n: u64) -> GetRawTransactionResultVin {
+    GetRawTransactionResultVin {
+      sequence: 0,
+      coinbase: None,
+      txid: Some(txid(n)),
+      vout: Some(0),
+      script_sig: None,
+      txinwitness: None,
+    }
+  }
+
+  /// A reveal spending a 10,000 sat commit output to a 546 sat output,
+  /// 200 vB in size.
+  fn reveal_tx_result() -> GetRawTransactionResult {
+    let mut raw_tx_result = raw_tx_result(2, &owner(1), 100);
+    raw_tx_result.vin = vec![input(1)];
+    raw_tx_result.vout[0].value = Amount::from_sat(546);
+    raw_tx_result.vsize = 200;
+    raw_tx_result
+  }
+
+  #[test]
+  fn fee_is_inputs_minus_outputs() {
+    assert_eq!(
+      compute_fee(&reveal_tx_result(), &[Amount::from_sat(10_000)]),
+      Some(9_454)
+    );
+  }
+
+  #[test]
+  fn fee_requires_every_input_value() {
+    assert_eq!(compute_fee(&reveal_tx_result(), &[]), None);
+  }
+
+  #[test]
+  fn fee_of_outputs_worth_more_than_inputs_is_unknown() {
+    assert_eq!(
+      compute_fee(&reveal_tx_result(), &[Amount::from_sat(500)]),
+      None
+    );
+  }
+
+  #[test]
+  fn fee_rate() {
+    let genesis = Genesis::new(58, &reveal_tx_result(), Some(&[Amount::from_sat(10_000)]));
+
+    assert_eq!(
+      genesis,
+      Genesis {
+        size: 58,
+        fee: Some(9_454),
+        vsize: 200,
+        fee_rate: Some(47.27),
+      }
+    );
+    assert_eq!(genesis.fee_rate, Some(47.27));
+
+    assert_eq!(Genesis::new(58, &reveal_tx_result(), None).fee_rate, None);
+  }
+
+  #[test]
+  fn fee_stats_weigh_fee_rates_by_size() {
+    let fee_stats = FeeStats::new(&[
+      Genesis {
+        size: 50,
+        fee: Some(1_000),
+        vsize: 100,
+        fee_rate: Some(10.0),
+      },
+      Genesis {
+        size: 60,
+        fee: Some(6_000),
+        vsize: 300,
+        fee_rate: Some(20.0),
+      },
+      Genesis {
+        size: 70,
+        fee: None,
+        vsize: 100,
+        fee_rate: None,
+      },
+    ]);
+
+    assert_eq!(
+      fee_stats,
+      FeeStats {
+        inscriptions: 3,
+        total_size: 180,
+        total_fee: 7_000,
+        average_fee_rate: Some(17.5),
+      }
+    );
+  }
+}
@@ -0,0 +1,1581 @@
+use super::*;
+
+const PAGE_SIZE: usize = 1000;
+
+const FETCH_THREADS: usize = 8;
+
+const BLOCK_CACHE_CAPACITY: usize = 100;
+
+/// Media types BRC-20 operations may be inscribed with. A charset parameter,
+/// if any, must be one of `CHARSETS`, other parameters are ignored.
+const MEDIA_TYPES: &[(&str, &str)] = &[("application", "json"), ("text", "plain")];
+
+const CHARSETS: &[&str] = &["utf-8", "us-ascii"];
+
+/// Why an inscription wasn't treated as a BRC-20 operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SkipReason {
+  FetchFailed,
+  InscriptionNotFound,
+  InvalidUtf8,
+  LocationNotFound,
+  NotBrc20,
+  TickerNotIndexed,
+  TooFewConfirmations,
+  UnsupportedContentType,
+  UnusableRevealTransaction,
+}
+
+impl Display for SkipReason {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::FetchFailed => write!(f, "Failed to fetch inscription"),
+      Self::InscriptionNotFound => write!(f, "Inscription not found"),
+      Self::InvalidUtf8 => write!(f, "Body is not valid UTF-8"),
+      Self::LocationNotFound => write!(f, "Inscription location not found"),
+      Self::NotBrc20 => write!(f, "Not a BRC-20 operation"),
+      Self::TickerNotIndexed => write!(f, "Ticker not indexed"),
+      Self::TooFewConfirmations => write!(f, "Reveal transaction has too few confirmations"),
+      Self::UnsupportedContentType => write!(f, "Unsupported content type"),
+      Self::UnusableRevealTransaction => write!(f, "Reveal transaction is unusable"),
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+struct ProgressReport {
+  processed: usize,
+  deploys: usize,
+  mints: usize,
+  transfers: usize,
+  invalid: usize,
+  rate: f64,
+}
+
+impl Display for ProgressReport {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "Processed {} inscriptions ({:.1}/s): {} deploys, {} mints, {} transfers, {} invalid",
+      self.processed, self.rate, self.deploys, self.mints, self.transfers, self.invalid,
+    )
+  }
+}
+
+/// Counts processed inscriptions, reporting every `interval` of them. An
+/// interval of zero disables reporting.
+struct Progress {
+  interval: usize,
+  processed: usize,
+  start: Instant,
+}
+
+impl Progress {
+  fn new(interval: usize, start: Instant) -> Self {
+    Self {
+      interval,
+      processed: 0,
+      start,
+    }
+  }
+
+  fn inscription_processed(
+    &mut self,
+    brc20_index: &Brc20Index,
+    now: Instant,
+  ) -> Option<ProgressReport> {
+    self.processed += 1;
+
+    if self.interval == 0 || self.processed % self.interval != 0 {
+      return None;
+    }
+
+    let elapsed = now.saturating_duration_since(self.start).as_secs_f64();
+
+    Some(ProgressReport {
+      processed: self.processed,
+      deploys: brc20_index.ticker_count(),
+      mints: brc20_index
+        .tickers()
+        .map(|ticker| ticker.get_mints().len())
+        .sum(),
+      transfers: brc20_index
+        .tickers()
+        .map(|ticker| ticker.get_transfers().len())
+        .sum(),
+      invalid: brc20_index.get_invalid_tx_map().len(),
+      rate: if elapsed > 0.0 {
+        self.processed as f64 / elapsed
+      } else {
+        0.0
+      },
+    })
+  }
+}
+
+/// Where a transaction was mined: the height of its block and its index
+/// within the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct TxPosition {
+  height: u64,
+  index: usize,
+}
+
+/// Heights and transaction ids of recently loaded blocks, shared by all
+/// fetch threads, so that sends mined in the same block only load it once.
+/// Cleared whenever it holds `BLOCK_CACHE_CAPACITY` blocks.
+#[derive(Default)]
+struct BlockCache(Mutex<HashMap<BlockHash, Arc<(u64, Vec<Txid>)>>>);
+
+impl BlockCache {
+  fn get_tx_position(
+    &self,
+    index: &Index,
+    block_hash: BlockHash,
+    txid: Txid,
+  ) -> Result<Option<TxPosition>> {
+    let cached = self.0.lock().unwrap().get(&block_hash).cloned();
+
+    let block = match cached {
+      Some(block) => block,
+      None => {
+        let Some(header_info) = index.block_header_info(block_hash)? else {
+          return Ok(None);
+        };
+
+        let Some(block) = index.get_block_by_hash(block_hash)? else {
+          return Ok(None);
+        };
+
+        let block = Arc::new((
+          u64::try_from(header_info.height)?,
+          block.txdata.iter().map(Transaction::txid).collect(),
+        ));
+
+        let mut blocks = self.0.lock().unwrap();
+        if blocks.len() >= BLOCK_CACHE_CAPACITY {
+          blocks.clear();
+        }
+        blocks.insert(block_hash, block.clone());
+
+        block
+      }
+    };
+
+    let (height, txids) = &*block;
+
+    Ok(
+      txids
+        .iter()
+        .position(|block_txid| *block_txid == txid)
+        .map(|index| TxPosition {
+          height: *height,
+          index,
+        }),
+    )
+  }
+}
+
+struct TransferSend {
+  tick: String,
+  sender: Owner,
+  spender: Owner,
+  inscription_id: InscriptionId,
+  send_tx: Brc20Tx,
+  sat_point: SatPoint,
+  position: TxPosition,
+}
+
+/// Pending sends, keyed by the position and vout of the send, so that they
+/// are applied in the order they were mined no matter which order their
+/// transfers were inscribed in. Blocktimes aren't monotonic, so sends are
+/// interleaved with inscriptions by height.
+#[derive(Default)]
+struct TransferSends(BTreeMap<(TxPosition, u32), Vec<TransferSend>>);
+
+impl TransferSends {
+  fn insert(&mut self, transfer_send: TransferSend) {
+    self
+      .0
+      .entry((transfer_send.position, transfer_send.send_tx.get_vout()))
+      .or_default()
+      .push(transfer_send);
+  }
+
+  /// Applies the sends mined below `height`. The position of an inscription
+  /// within its block isn't known, so sends mined in the same block as an
+  /// inscription are applied after it.
+  fn apply_until(&mut self, brc20_index: &mut Brc20Index, height: u64) {
+    let later = self.0.split_off(&(TxPosition { height, index: 0 }, 0));
+    Self(std::mem::replace(&mut self.0, later)).apply(brc20_index);
+  }
+
+  fn apply(self, brc20_index: &mut Brc20Index) {
+    for transfer_send in self.0.into_values().flatten() {
+      brc20_index.process_transfer_send(
+        &transfer_send.tick,
+        &transfer_send.sender,
+        &transfer_send.spender,
+        transfer_send.inscription_id,
+        transfer_send.send_tx,
+        transfer_send.sat_point,
+      );
+    }
+  }
+}
+
+/// How `index_brc20` indexes and validates operations.
+pub(crate) struct IndexerOptions {
+  /// Only index operations on these tickers, expected to be lowercase.
+  pub(crate) tickers: Option<HashSet<String>>,
+  /// Append validation outcomes to this file.
+  pub(crate) log_file: Option<PathBuf>,
+  /// Keep skipped inscriptions along with the reason, not only their count.
+  pub(crate) record_skipped: bool,
+  /// Fail on inscriptions listed by number that can't be found by id.
+  pub(crate) strict: bool,
+  /// Skip inscriptions whose reveal transaction has fewer confirmations, so
+  /// that reorgs near the tip don't churn balances.
+  pub(crate) min_confirmations: u32,
+  /// Log progress every this many inscriptions.
+  pub(crate) progress_interval: usize,
+  pub(crate) ticker_length_policy: TickerLengthPolicy,
+  pub(crate) protocols: ProtocolAllowlist,
+  pub(crate) default_decimals: u8,
+  pub(crate) webhook: Option<Webhook>,
+  /// Count processed inscriptions and record the finished index and the
+  /// height it was built from.
+  pub(crate) metrics: Option<Arc<Metrics>>,
+  /// Index this many ranges of inscription numbers on separate threads and
+  /// merge the results. Validation outcomes of shards aren't logged or sent
+  /// to the webhook.
+  pub(crate) shards: usize,
+}
+
+/// Indexes every BRC-20 operation, as configured by `options`. Raw
+/// transaction info is fetched from `tx_provider` through `tx_info_cache`.
+///
+/// Operations are applied in inscription number order, which follows block
+/// height and then position within the block, so when two inscriptions
+/// share a blocktime, the one mined first wins, for example for the last
+/// tokens of a ticker.
+pub(crate) fn index_brc20(
+  index: &Index,
+  network: Network,
+  options: IndexerOptions,
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+) -> Result<Brc20Index> {
+  let IndexerOptions {
+    tickers,
+    log_file,
+    record_skipped,
+    strict,
+    min_confirmations,
+    progress_interval,
+    ticker_length_policy,
+    protocols,
+    default_decimals,
+    webhook,
+    metrics,
+    shards,
+  } = options;
+
+  let height = index.height()?.map(|height| height.n()).unwrap_or_default();
+
+  if let Some(metrics) = &metrics {
+    metrics.indexing(height);
+  }
+
+  let configure = |brc20_index: &mut Brc20Index| {
+    if record_skipped {
+      brc20_index.record_skipped();
+    }
+
+    brc20_index.set_ticker_length_policy(ticker_length_policy.clone());
+    brc20_index.set_protocols(protocols.clone());
+    brc20_index.set_default_decimals(default_decimals);
+  };
+
+  let mut brc20_index = match log_file {
+    Some(log_file) => Brc20Index::with_validation_log(ValidationLog::new(log_file)),
+    None => Brc20Index::new(),
+  };
+
+  configure(&mut brc20_index);
+
+  if let Some(webhook) = webhook {
+    brc20_index.send_events_to(webhook);
+  }
+
+  let shard = Shard {
+    index,
+    network,
+    tickers: tickers.as_ref(),
+    strict,
+    min_confirmations,
+    progress_interval,
+    tx_provider,
+    tx_info_cache,
+    metrics: metrics.as_deref(),
+  };
+
+  // Cursed inscriptions have negative numbers and are not BRC-20 operations,
+  // so indexing starts at inscription zero.
+  let Some(&(last, _)) = index.get_feed_inscriptions(1)?.first() else {
+    return Ok(finish(brc20_index, metrics.as_deref(), height));
+  };
+
+  if shards <= 1 || last < 0 {
+    shard.index(&mut brc20_index, 0..=last)?;
+    return Ok(finish(brc20_index, metrics.as_deref(), height));
+  }
+
+  let shards = i64::try_from(shards)?;
+  let shard_size = (last + shards) / shards;
+
+  let indexed = thread::scope(|scope| {
+    let handles = (0..=last)
+      .step_by(usize::try_from(shard_size)?)
+      .map(|start| {
+        let shard = &shard;
+        let configure = &configure;
+        scope.spawn(move || -> Result<Brc20Index> {
+          let mut brc20_index = Brc20Index::new();
+          configure(&mut brc20_index);
+          shard.index(&mut brc20_index, start..=(start + shard_size - 1).min(last))?;
+          Ok(brc20_index)
+        })
+      })
+      .collect::<Vec<_>>();
+
+    handles
+      .into_iter()
+      .map(|handle| {
+        handle
+          .join()
+          .map_err(|_| anyhow!("BRC-20 indexing thread panicked"))?
+      })
+      .collect::<Result<Vec<Brc20Index>>>()
+  })?;
+
+  for shard in indexed {
+    brc20_index.merge(shard);
+  }
+
+  Ok(finish(brc20_index, metrics.as_deref(), height))
+}
+
+/// Records `brc20_index`, built from the ord index at `height`, in `metrics`.
+fn finish(brc20_index: Brc20Index, metrics: Option<&Metrics>, height: u64) -> Brc20Index {
+  if let Some(metrics) = metrics {
+    metrics.indexed(&brc20_index, height);
+  }
+
+  brc20_index
+}
+
+/// What indexing a range of inscription numbers needs.
+struct Shard<'a> {
+  index: &'a Index,
+  network: Network,
+  tickers: Option<&'a HashSet<String>>,
+  strict: bool,
+  min_confirmations: u32,
+  progress_interval: usize,
+  tx_provider: &'a dyn TxProvider,
+  tx_info_cache: &'a TxInfoCache,
+  metrics: Option<&'a Metrics>,
+}
+
+impl Shard<'_> {
+  /// Applies the operations of the inscriptions numbered `range` to
+  /// `brc20_index`.
+  fn index(&self, brc20_index: &mut Brc20Index, range: RangeInclusive<i64>) -> Result {
+    let mut transfer_sends = TransferSends::default();
+
+    let block_cache = BlockCache::default();
+
+    let mut progress = Progress::new(self.progress_interval, Instant::now());
+
+    let mut from = *range.start();
+
+    while from <= *range.end() {
+      let mut inscription_ids = self.index.get_inscription_ids_by_number(from, PAGE_SIZE)?;
+
+      inscription_ids.retain(|(inscription_number, _)| range.contains(inscription_number));
+
+      let Some((last, _)) = inscription_ids.last() else {
+        break;
+      };
+
+      from = last + 1;
+
+      for (inscription_id, fetched) in
+        fetch_page(&inscription_ids, |inscription_number, inscription_id| {
+          fetch_inscription(
+            self.index,
+            self.tx_provider,
+            self.tx_info_cache,
+            &block_cache,
+            self.network,
+            self.tickers,
+            self.min_confirmations,
+            inscription_number,
+            inscription_id,
+          )
+        })?
+      {
+        match fetched {
+          Ok(fetched) => apply(brc20_index, &mut transfer_sends, fetched),
+          Err(reason) => skip(brc20_index, inscription_id, reason, self.strict)?,
+        }
+
+        if let Some(metrics) = self.metrics {
+          metrics.inscription_processed();
+        }
+
+        if let Some(report) = progress.inscription_processed(brc20_index, Instant::now()) {
+          log::info!("{report}");
+        }
+      }
+    }
+
+    transfer_sends.apply(brc20_index);
+
+    Ok(())
+  }
+}
+
+/// An inscription listed by number that can't be found by id means the ord
+/// index is inconsistent, so it's warned about and counted separately from
+/// other skips, or is an error in strict mode.
+fn skip(
+  brc20_index: &mut Brc20Index,
+  inscription_id: InscriptionId,
+  reason: SkipReason,
+  strict: bool,
+) -> Result {
+  if reason == SkipReason::InscriptionNotFound {
+    if strict {
+      bail!("inscription {inscription_id} is listed by number but not found, index may be corrupt");
+    }
+
+    log::warn!("inscription {inscription_id} is listed by number but not found, skipping");
+    brc20_index.add_missing_inscription();
+  }
+
+  brc20_index.add_skipped(inscription_id, reason.to_string());
+
+  Ok(())
+}
+
+/// Everything needed to apply the BRC-20 operations of an inscription,
+/// loaded ahead of time so that RPC calls for a page of inscriptions can
+/// overlap.
+struct Fetched {
+  inscription_id: InscriptionId,
+  inscription_number: i64,
+  height: u64,
+  operations: Vec<Result<Brc20Operation, (Brc20Operation, Brc20Error)>>,
+  brc20_tx: Brc20Tx,
+  genesis: Genesis,
+  sat_point: SatPoint,
+  transfer_send: Option<(Owner, Brc20Tx, SatPoint, TxPosition)>,
+}
+
+/// Fetches a page of inscriptions on up to `FETCH_THREADS` threads,
+/// returning results in the same order as `inscription_ids`. An error
+/// fetching one inscription is logged and the inscription skipped, so that
+/// a single bad transaction doesn't abort indexing.
+#[allow(clippy::type_complexity)]
+fn fetch_page(
+  inscription_ids: &[(i64, InscriptionId)],
+  fetch: impl Fn(i64, InscriptionId) -> Result<Result<Fetched, SkipReason>> + Sync,
+) -> Result<Vec<(InscriptionId, Result<Fetched, SkipReason>)>> {
+  let chunk_size = (inscription_ids.len() + FETCH_THREADS - 1) / FETCH_THREADS;
+  let fetch = &fetch;
+
+  thread::scope(|scope| {
+    let handles = inscription_ids
+      .chunks(chunk_size.max(1))
+      .map(|chunk| {
+        scope.spawn(move || {
+          chunk
+            .iter()
+            .map(|(inscription_number, inscription_id)| {
+              let fetched = fetch(*inscription_number, *inscription_id).unwrap_or_else(|err| {
+                log::warn!("failed to fetch inscription {inscription_id}, skipping: {err:#}");
+                Err(SkipReason::FetchFailed)
+              });
+
+              (*inscription_id, fetched)
+            })
+            .collect::<Vec<_>>()
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let mut fetched = Vec::with_capacity(inscription_ids.len());
+
+    for handle in handles {
+      fetched.extend(
+        handle
+          .join()
+          .map_err(|_| anyhow!("inscription fetch thread panicked"))?,
+      );
+    }
+
+    Ok(fetched)
+  })
+}
+
+fn fetch_inscription(
+  index: &Index,
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  block_cache: &BlockCache,
+  network: Network,
+  tickers: Option<&HashSet<String>>,
+  min_confirmations: u32,
+  inscription_number: i64,
+  inscription_id: InscriptionId,
+) -> Result<Result<Fetched, SkipReason>> {
+  let Some(inscription) = index.get_inscription_by_id(inscription_id)? else {
+    return Ok(Err(SkipReason::InscriptionNotFound));
+  };
+
+  let Some(entry) = index.get_inscription_entry(inscription_id)? else {
+    return Ok(Err(SkipReason::InscriptionNotFound));
+  };
+
+  let body = match brc20_body(&inscription) {
+    Ok(body) => body,
+    Err(reason) => return Ok(Err(reason)),
+  };
+
+  let mut operations = Brc20Operation::parse_all(body);
+
+  if operations.is_empty() {
+    return Ok(Err(SkipReason::NotBrc20));
+  }
+
+  if let Some(tickers) = tickers {
+    operations.retain(|(Ok(operation) | Err((operation, _)))| {
+      tickers.contains(&operation.tick().to_lowercase())
+    });
+
+    if operations.is_empty() {
+      return Ok(Err(SkipReason::TickerNotIndexed));
+    }
+  }
+
+  if !has_confirmations(
+    tx_provider,
+    tx_info_cache,
+    inscription_id,
+    min_confirmations,
+  )? {
+    return Ok(Err(SkipReason::TooFewConfirmations));
+  }
+
+  let Some(sat_point) = index.get_inscription_satpoint_by_id(inscription_id)? else {
+    return Ok(Err(SkipReason::LocationNotFound));
+  };
+
+  let is_transfer = operations
+    .iter()
+    .any(|operation| matches!(operation, Ok(Brc20Operation::Transfer(_))));
+
+  // Deploys and mints belong to the owner of the reveal output the
+  // inscription landed in. A transfer belongs to its inscriber, the owner of
+  // the first output, and was sent by the reveal if it landed elsewhere.
+  let vout = if is_transfer {
+    None
+  } else {
+    let Some(reveal_satpoint) =
+      get_reveal_satpoint(tx_provider, tx_info_cache, inscription_id, sat_point)?
+    else {
+      return Ok(Err(SkipReason::LocationNotFound));
+    };
+
+    Some(reveal_satpoint.outpoint.vout)
+  };
+
+  let Some(brc20_tx) = get_brc20_tx(tx_provider, tx_info_cache, network, inscription_id, vout)?
+  else {
+    return Ok(Err(SkipReason::UnusableRevealTransaction));
+  };
+
+  let genesis = get_genesis(tx_provider, tx_info_cache, inscription_id, body.len())?;
+
+  let transfer_send = if is_transfer {
+    match get_transfer_send(
+      index,
+      tx_provider,
+      tx_info_cache,
+      network,
+      inscription_id,
+      brc20_tx.get_owner(),
+    )? {
+      Some((spender, send_tx, sat_point)) => {
+        get_send_position(index, tx_provider, tx_info_cache, block_cache, &send_tx)?.map(
+          |position| {
+            (
+              spender,
+              send_tx.with_height(position.height),
+              sat_point,
+              position,
+            )
+          },
+        )
+      }
+      None => None,
+    }
+  } else {
+    None
+  };
+
+  Ok(Ok(Fetched {
+    inscription_id,
+    inscription_number,
+    height: entry.height,
+    operations,
+    brc20_tx: brc20_tx.with_height(entry.height),
+    genesis,
+    sat_point,
+    transfer_send,
+  }))
+}
+
+fn apply(brc20_index: &mut Brc20Index, transfer_sends: &mut TransferSends, fetched: Fetched) {
+  let Fetched {
+    inscription_id,
+    inscription_number,
+    height,
+    operations,
+    brc20_tx,
+    genesis,
+    sat_point,
+    mut transfer_send,
+  } = fetched;
+
+  transfer_sends.apply_until(brc20_index, height);
+
+  brc20_index.record_genesis(inscription_id, genesis);
+
+  // Inscriptions on the same sat share its current location. An ignored
+  // reinscription is recorded under its first operation.
+  let (Ok(first) | Err((first, _))) = &operations[0];
+  if !brc20_index.claim_sat(
+    inscription_id,
+    first.op(),
+    first.tick(),
+    brc20_tx.clone(),
+    sat_point,
+  ) {
+    return;
+  }
+
+  for operation in operations {
+    let brc20_tx = brc20_tx.clone();
+
+    let operation = match operation {
+      Ok(operation) => operation,
+      Err((operation, err)) => {
+        brc20_index.reject_operation(
+          inscription_id,
+          operation.op(),
+          operation.tick(),
+          brc20_tx,
+          err,
+        );
+        continue;
+      }
+    };
+
+    match operation {
+      Brc20Operation::Deploy(deploy_script) => {
+        brc20_index.process_deploy(inscription_id, inscription_number, brc20_tx, deploy_script);
+      }
+      Brc20Operation::Mint(mint_script) => {
+        brc20_index.process_mint(inscription_id, inscription_number, brc20_tx, mint_script);
+      }
+      Brc20Operation::Transfer(transfer_script) => {
+        let tick = transfer_script.tick.clone();
+        let sender = brc20_tx.get_owner().clone();
+
+        let valid = brc20_index.process_transfer(
+          inscription_id,
+          inscription_number,
+          brc20_tx,
+          transfer_script,
+        );
+
+        if let Some((spender, send_tx, sat_point, position)) = transfer_send.take() {
+          if valid {
+            transfer_sends.insert(TransferSend {
+              tick,
+              sender,
+              spender,
+              inscription_id,
+              send_tx,
+              sat_point,
+              position,
+            });
+          } else {
+            brc20_index.keep_rejected_send(
+              &tick,
+              &sender,
+              &spender,
+              inscription_id,
+              send_tx,
+              sat_point,
+            );
+          }
+        }
+      }
+    }
+  }
+}
+
+fn brc20_body(inscription: &Inscription) -> Result<&str, SkipReason> {
+  if !inscription
+    .content_type()
+    .map(is_brc20_content_type)
+    .unwrap_or_default()
+  {
+    return Err(SkipReason::UnsupportedContentType);
+  }
+
+  std::str::from_utf8(inscription.body().unwrap_or_default()).map_err(|_| SkipReason::InvalidUtf8)
+}
+
+fn is_brc20_content_type(content_type: &str) -> bool {
+  let Ok(mime) = content_type.parse::<mime::Mime>() else {
+    return false;
+  };
+
+  MEDIA_TYPES.contains(&(mime.type_().as_str(), mime.subtype().as_str()))
+    && mime
+      .get_param(mime::CHARSET)
+      .map(|charset| CHARSETS.contains(&charset.as_str().to_lowercase().as_str()))
+      .unwrap_or(true)
+}
+
+/// Returns `None` when the reveal transaction can't be turned into a
+/// `Brc20Tx`, in which case the inscription is skipped. The owner is that of
+/// output `vout` if given, or of the first output.
+fn get_brc20_tx(
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  network: Network,
+  inscription_id: InscriptionId,
+  vout: Option<u32>,
+) -> Result<Option<Brc20Tx>> {
+  let raw_tx_result = tx_info_cache
+    .get_raw_transaction_info(tx_provider, inscription_id.txid)?
+    .ok_or_else(|| anyhow!("transaction {} not found", inscription_id.txid))?;
+
+  Ok(to_brc20_tx(inscription_id, &raw_tx_result, vout, network))
+}
+
+/// Whether the reveal transaction of `inscription_id` has at least
+/// `min_confirmations` confirmations. Every inscription counts if
+/// `min_confirmations` is zero.
+fn has_confirmations(
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  inscription_id: InscriptionId,
+  min_confirmations: u32,
+) -> Result<bool> {
+  if min_confirmations == 0 {
+    return Ok(true);
+  }
+
+  let raw_tx_result = tx_info_cache
+    .get_raw_transaction_info(tx_provider, inscription_id.txid)?
+    .ok_or_else(|| anyhow!("transaction {} not found", inscription_id.txid))?;
+
+  Ok(raw_tx_result.confirmations.unwrap_or_default() >= min_confirmations)
+}
+
+/// The reveal transaction is already cached by `get_brc20_tx`, but the
+/// transactions its inputs spend are fetched to compute its fee, which is
+/// left unknown if any of them can't be found.
+fn get_genesis(
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  inscription_id: InscriptionId,
+  size: usize,
+) -> Result<Genesis> {
+  let raw_tx_result = tx_info_cache
+    .get_raw_transaction_info(tx_provider, inscription_id.txid)?
+    .ok_or_else(|| anyhow!("transaction {} not found", inscription_id.txid))?;
+
+  let mut input_values = Vec::with_capacity(raw_tx_result.vin.len());
+
+  for input in &raw_tx_result.vin {
+    let (Some(txid), Some(vout)) = (input.txid, input.vout) else {
+      return Ok(Genesis::new(size, &raw_tx_result, None));
+    };
+
+    let Some(value) = tx_info_cache
+      .get_raw_transaction_info(tx_provider, txid)?
+      .and_then(|spent_raw_tx_result| {
+        usize::try_from(vout)
+          .ok()
+          .and_then(|vout| spent_raw_tx_result.vout.get(vout))
+          .map(|output| output.value)
+      })
+    else {
+      return Ok(Genesis::new(size, &raw_tx_result, None));
+    };
+
+    input_values.push(value);
+  }
+
+  Ok(Genesis::new(size, &raw_tx_result, Some(&input_values)))
+}
+
+/// Unconfirmed reveal transactions have no blocktime yet and are expected,
+/// so they're only logged at debug level.
+fn to_brc20_tx(
+  inscription_id: InscriptionId,
+  raw_tx_result: &GetRawTransactionResult,
+  vout: Option<u32>,
+  network: Network,
+) -> Option<Brc20Tx> {
+  let brc20_tx = match vout {
+    Some(vout) => Brc20Tx::for_output(raw_tx_result, vout, network),
+    None => Brc20Tx::new(raw_tx_result, network),
+  };
+
+  match brc20_tx {
+    Ok(brc20_tx) => Some(brc20_tx),
+    Err(Brc20Error::BlocktimeNotFound) => {
+      log::debug!("skipping unconfirmed inscription {inscription_id}");
+      None
+    }
+    Err(err) => {
+      log::warn!("skipping inscription {inscription_id}: {err}");
+      None
+    }
+  }
+}
+
+/// A transfer inscription that no longer sits in its reveal transaction has
+/// been sent. Its sat is followed back to the transaction that spent it out
+/// of the reveal transaction, whose input owner is the spender, and the
+/// owner of the output the sat landed in is the receiver. A transfer
+/// inscribed straight to an output not owned by `inscriber` was sent by its
+/// reveal transaction, no matter where the sat went afterwards.
+fn get_transfer_send(
+  index: &Index,
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  network: Network,
+  inscription_id: InscriptionId,
+  inscriber: &Owner,
+) -> Result<Option<(Owner, Brc20Tx, SatPoint)>> {
+  let Some(mut satpoint) = index.get_inscription_satpoint_by_id(inscription_id)? else {
+    return Ok(None);
+  };
+
+  loop {
+    if satpoint.outpoint.txid == inscription_id.txid {
+      return get_reveal_send(tx_provider, tx_info_cache, network, inscriber, satpoint);
+    }
+
+    let Some(raw_tx_result) =
+      tx_info_cache.get_raw_transaction_info(tx_provider, satpoint.outpoint.txid)?
+    else {
+      return Ok(None);
+    };
+
+    let Some((spent, spent_raw_tx_result, offset)) =
+      get_spent_output(tx_provider, tx_info_cache, &raw_tx_result, satpoint)?
+    else {
+      return Ok(None);
+    };
+
+    if spent.txid == inscription_id.txid {
+      let reveal_satpoint = SatPoint {
+        outpoint: spent,
+        offset,
+      };
+
+      if let Some(reveal_send) =
+        to_reveal_send(&spent_raw_tx_result, reveal_satpoint, inscriber, network)
+      {
+        return Ok(Some(reveal_send));
+      }
+
+      let Ok(spender) = get_owner_of_output(&spent, &spent_raw_tx_result, network) else {
+        return Ok(None);
+      };
+
+      return Ok(
+        Brc20Tx::for_output(&raw_tx_result, satpoint.outpoint.vout, network)
+          .ok()
+          .map(|send_tx| (spender, send_tx, satpoint)),
+      );
+    }
+
+    satpoint = SatPoint {
+      outpoint: spent,
+      offset,
+    };
+  }
+}
+
+/// Sends whose block can't be found are dropped, since they can't be
+/// ordered.
+fn get_send_position(
+  index: &Index,
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  block_cache: &BlockCache,
+  send_tx: &Brc20Tx,
+) -> Result<Option<TxPosition>> {
+  let Some(block_hash) = tx_info_cache
+    .get_raw_transaction_info(tx_provider, send_tx.get_txid())?
+    .and_then(|raw_tx_result| raw_tx_result.blockhash)
+  else {
+    return Ok(None);
+  };
+
+  block_cache.get_tx_position(index, block_hash, send_tx.get_txid())
+}
+
+/// Follows the sat at `satpoint` back to the output of the reveal
+/// transaction of `inscription_id` it was inscribed in.
+fn get_reveal_satpoint(
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  inscription_id: InscriptionId,
+  mut satpoint: SatPoint,
+) -> Result<Option<SatPoint>> {
+  while satpoint.outpoint.txid != inscription_id.txid {
+    let Some(raw_tx_result) =
+      tx_info_cache.get_raw_transaction_info(tx_provider, satpoint.outpoint.txid)?
+    else {
+      return Ok(None);
+    };
+
+    let Some((spent, _, offset)) =
+      get_spent_output(tx_provider, tx_info_cache, &raw_tx_result, satpoint)?
+    else {
+      return Ok(None);
+    };
+
+    satpoint = SatPoint {
+      outpoint: spent,
+      offset,
+    };
+  }
+
+  Ok(Some(satpoint))
+}
+
+fn get_reveal_send(
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  network: Network,
+  inscriber: &Owner,
+  satpoint: SatPoint,
+) -> Result<Option<(Owner, Brc20Tx, SatPoint)>> {
+  Ok(
+    tx_info_cache
+      .get_raw_transaction_info(tx_provider, satpoint.outpoint.txid)?
+      .and_then(|raw_tx_result| to_reveal_send(&raw_tx_result, satpoint, inscriber, network)),
+  )
+}
+
+/// The reveal transaction is also the send when the output at `satpoint`,
+/// which carries the inscription, is owned by someone other than
+/// `inscriber`, the owner of its first output.
+fn to_reveal_send(
+  raw_tx_result: &GetRawTransactionResult,
+  satpoint: SatPoint,
+  inscriber: &Owner,
+  network: Network,
+) -> Option<(Owner, Brc20Tx, SatPoint)> {
+  let send_tx = Brc20Tx::for_output(raw_tx_result, satpoint.outpoint.vout, network).ok()?;
+
+  (send_tx.get_owner() != inscriber).then(|| (inscriber.clone(), send_tx, satpoint))
+}
+
+/// Finds the input of `raw_tx_result` that carried the sat at `satpoint`,
+/// returning the output it spent, that output's transaction, and the sat's
+/// offset within it.
+fn get_spent_output(
+  tx_provider: &dyn TxProvider,
+  tx_info_cache: &TxInfoCache,
+  raw_tx_result: &GetRawTransactionResult,
+  satpoint: SatPoint,
+) -> Result<Option<(OutPoint, GetRawTransactionResult, u64)>> {
+  let Some(outputs) = usize::try_from(satpoint.outpoint.vout)
+    .ok()
+    .and_then(|vout| raw_tx_result.vout.get(..vout))
+  else {
+    return Ok(None);
+  };
+
+  let mut offset = outputs
+    .iter()
+    .map(|output| output.value.to_sat())
+    .sum::<u64>()
+    + satpoint.offset;
+
+  for input in &raw_tx_result.vin {
+    let (Some(txid), Some(vout)) = (input.txid, input.vout) else {
+      return Ok(None);
+    };
+
+    let Some(spent_raw_tx_result) = tx_info_cache.get_raw_transaction_info(tx_provider, txid)?
+    else {
+      return Ok(None);
+    };
+
+    let Some(value) = usize::try_from(vout)
+      .ok()
+      .and_then(|vout| spent_raw_tx_result.vout.get(vout))
+      .map(|output| output.value.to_sat())
+    else {
+      return Ok(None);
+    };
+
+    if offset < value {
+      return Ok(Some((OutPoint { txid, vout }, spent_raw_tx_result, offset)));
+    }
+
+    offset -= value;
+  }
+
+  Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, bitcoincore_rpc::bitcoincore_rpc_json::GetRawTransactionResultVin};
+
+  #[test]
+  fn progress_is_reported_every_interval() {
+    let start = Instant::now();
+    let mut progress = Progress::new(3, start);
+
+    let mut brc20_index = Brc20Index::new();
+    assert!(brc20_index.process_deploy(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000"}"#).unwrap(),
+    ));
+
+    let reports = (1..=7)
+      .map(|n| {
+        progress
+          .inscription_processed(&brc20_index, start + Duration::from_secs(n))
+          .map(|report| (n, report))
+      })
+      .collect::<Vec<Option<(u64, ProgressReport)>>>();
+
+    assert_eq!(
+      reports
+        .iter()
+        .flatten()
+        .map(|(n, report)| (*n, report.processed))
+        .collect::<Vec<(u64, usize)>>(),
+      [(3, 3), (6, 6)]
+    );
+
+    let (_, report) = reports[2].as_ref().unwrap();
+    assert_eq!(report.rate, 1.0);
+    assert_eq!(report.deploys, 1);
+    assert_eq!(
+      report.to_string(),
+      "Processed 3 inscriptions (1.0/s): 1 deploys, 0 mints, 0 transfers, 0 invalid"
+    );
+  }
+
+  #[test]
+  fn zero_progress_interval_disables_reports() {
+    let start = Instant::now();
+    let mut progress = Progress::new(0, start);
+
+    for n in 1..=10 {
+      assert_eq!(
+        progress.inscription_processed(&Brc20Index::new(), start + Duration::from_secs(n)),
+        None
+      );
+    }
+  }
+
+  #[test]
+  fn missing_inscription_is_counted_or_fails_in_strict_mode() {
+    let mut brc20_index = Brc20Index::new();
+
+    skip(
+      &mut brc20_index,
+      inscription_id(1),
+      SkipReason::NotBrc20,
+      false,
+    )
+    .unwrap();
+    skip(
+      &mut brc20_index,
+      inscription_id(2),
+      SkipReason::InscriptionNotFound,
+      false,
+    )
+    .unwrap();
+
+    assert_eq!(brc20_index.get_skipped_count(), 2);
+    assert_eq!(brc20_index.get_missing_inscription_count(), 1);
+
+    skip(
+      &mut brc20_index,
+      inscription_id(3),
+      SkipReason::NotBrc20,
+      true,
+    )
+    .unwrap();
+
+    assert_eq!(
+      skip(
+        &mut brc20_index,
+        inscription_id(4),
+        SkipReason::InscriptionNotFound,
+        true,
+      )
+      .unwrap_err()
+      .to_string(),
+      format!(
+        "inscription {} is listed by number but not found, index may be corrupt",
+        inscription_id(4)
+      )
+    );
+    assert_eq!(brc20_index.get_missing_inscription_count(), 1);
+  }
+
+  #[test]
+  fn failed_fetch_skips_only_that_inscription() {
+    let inscription_ids = (1..=5)
+      .map(|n| (i64::from(n), inscription_id(n)))
+      .collect::<Vec<(i64, InscriptionId)>>();
+
+    let fetched = fetch_page(&inscription_ids, |inscription_number, inscription_id| {
+      if inscription_number == 3 {
+        bail!("transaction {} not found", inscription_id.txid);
+      }
+
+      Ok(Err(SkipReason::NotBrc20))
+    })
+    .unwrap();
+
+    assert_eq!(
+      fetched
+        .into_iter()
+        .map(|(inscription_id, fetched)| (inscription_id, fetched.err()))
+        .collect::<Vec<(InscriptionId, Option<SkipReason>)>>(),
+      [
+        (inscription_id(1), Some(SkipReason::NotBrc20)),
+        (inscription_id(2), Some(SkipReason::NotBrc20)),
+        (inscription_id(3), Some(SkipReason::FetchFailed)),
+        (inscription_id(4), Some(SkipReason::NotBrc20)),
+        (inscription_id(5), Some(SkipReason::NotBrc20)),
+      ]
+    );
+  }
+
+  #[test]
+  fn brc20_body_requires_text_content_type() {
+    let body = r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}"#;
+
+    assert_eq!(
+      brc20_body(&inscription("text/plain;charset=utf-8", body)),
+      Ok(body)
+    );
+    assert_eq!(brc20_body(&inscription("application/json", body)), Ok(body));
+    assert_eq!(
+      brc20_body(&inscription("image/png", body)),
+      Err(SkipReason::UnsupportedContentType)
+    );
+    assert_eq!(
+      SkipReason::UnsupportedContentType.to_string(),
+      "Unsupported content type"
+    );
+  }
+
+  #[test]
+  fn content_type_parameters_other_than_charset_are_ignored() {
+    for content_type in [
+      "application/json",
+      "application/json; charset=utf-8",
+      "application/json;charset=UTF-8",
+      "text/plain",
+      "text/plain;charset=us-ascii",
+      "text/plain; charset=\"utf-8\"",
+      "TEXT/PLAIN; format=flowed; charset=utf-8",
+    ] {
+      assert!(is_brc20_content_type(content_type), "{content_type}");
+    }
+
+    for content_type in [
+      "",
+      "json",
+      "text/html;charset=utf-8",
+      "application/json;charset=utf-16",
+      "text/plain;charset=iso-8859-1",
+      "image/png",
+    ] {
+      assert!(!is_brc20_content_type(content_type), "{content_type}");
+    }
+  }
+
+  #[test]
+  fn brc20_body_requires_utf8() {
+    assert_eq!(
+      brc20_body(&inscription("application/json", [0xff, 0xfe])),
+      Err(SkipReason::InvalidUtf8)
+    );
+  }
+
+  #[test]
+  fn unconfirmed_inscriptions_are_skipped() {
+    let mut raw_tx_result = raw_tx_result(1, &owner(1), 100);
+    assert!(to_brc20_tx(inscription_id(1), &raw_tx_result, None, Network::Bitcoin).is_some());
+
+    raw_tx_result.blocktime = None;
+    assert_eq!(
+      to_brc20_tx(inscription_id(1), &raw_tx_result, None, Network::Bitcoin),
+      None
+    );
+  }
+
+  #[test]
+  fn inscriptions_need_min_confirmations_to_count() {
+    let mut raw_tx_result = raw_tx_result(1, &owner(1), 100);
+    raw_tx_result.confirmations = Some(0);
+
+    let tx_provider = LocalTxProvider::new([raw_tx_result]);
+    let tx_info_cache = TxInfoCache::new(10);
+
+    let has_confirmations = |min_confirmations| {
+      has_confirmations(
+        &tx_provider,
+        &tx_info_cache,
+        inscription_id(1),
+        min_confirmations,
+      )
+      .unwrap()
+    };
+
+    assert!(has_confirmations(0));
+    assert!(!has_confirmations(1));
+  }
+
+  #[test]
+  fn reveal_transactions_can_be_fetched_from_local_provider() {
+    let tx_provider = LocalTxProvider::new([raw_tx_result(1, &owner(1), 100)]);
+    let tx_info_cache = TxInfoCache::new(10);
+
+    let brc20_tx = get_brc20_tx(
+      &tx_provider,
+      &tx_info_cache,
+      Network::Bitcoin,
+      inscription_id(1),
+      None,
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(brc20_tx.get_txid(), txid(1));
+    assert_eq!(brc20_tx.get_owner(), &owner(1));
+    assert_eq!(brc20_tx.get_blocktime(), 100);
+
+    assert!(get_brc20_tx(
+      &tx_provider,
+      &tx_info_cache,
+      Network::Bitcoin,
+      inscription_id(2),
+      None,
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn transfer_sends_in_the_same_block_are_ordered_by_position() {
+    let mut transfer_sends = TransferSends::default();
+
+    for (n, index) in [(6, 0), (4, 2), (5, 1)] {
+      transfer_sends.insert(TransferSend {
+        tick: "ordi".into(),
+        sender: owner(1),
+        spender: owner(1),
+        inscription_id: inscription_id(n),
+        send_tx: brc20_tx(n.into(), &owner(2), 400),
+        sat_point: sat_point(n.into()),
+        position: TxPosition { height: 4, index },
+      });
+    }
+
+    assert_eq!(
+      transfer_sends
+        .0
+        .into_values()
+        .flatten()
+        .map(|transfer_send| transfer_send.inscription_id)
+        .collect::<Vec<InscriptionId>>(),
+      [inscription_id(6), inscription_id(5), inscription_id(4)]
+    );
+  }
+
+  #[test]
+  fn transfer_sends_are_applied_in_height_order() {
+    let mut brc20_index = Brc20Index::new();
+
+    assert!(brc20_index.process_deploy(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000"}"#).unwrap(),
+    ));
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(1), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+    assert!(brc20_index.process_transfer(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(1), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"40"}"#).unwrap(),
+    ));
+
+    let mut transfer_sends = TransferSends::default();
+    transfer_sends.insert(TransferSend {
+      tick: "ordi".into(),
+      sender: owner(1),
+      spender: owner(1),
+      inscription_id: inscription_id(3),
+      send_tx: brc20_tx(4, &owner(2), 500),
+      sat_point: sat_point(4),
+      position: TxPosition {
+        height: 4,
+        index: 1,
+      },
+    });
+
+    transfer_sends.apply_until(&mut brc20_index, 4);
+    assert!(brc20_index
+      .get_ticker("ordi")
+      .unwrap()
+      .get_user_balance(&owner(2))
+      .is_none());
+
+    // Blocktimes aren't monotonic, so a later block can have an earlier
+    // blocktime than the send.
+    transfer_sends.apply_until(&mut brc20_index, 5);
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(1))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("60", 18)
+    );
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("40", 18)
+    );
+  }
+
+  fn reveal_tx_result_with_second_output(receiver: &Owner) -> GetRawTransactionResult {
+    let mut raw_tx_result = raw_tx_result(3, &owner(1), 300);
+    let mut output = raw_tx_result.vout[0].clone();
+    output.n = 1;
+    output.script_pub_key.hex = script_pubkey(receiver).to_bytes();
+    raw_tx_result.vout.push(output);
+    raw_tx_result
+  }
+
+  fn reveal_satpoint(vout: u32) -> SatPoint {
+    SatPoint {
+      outpoint: OutPoint {
+        txid: txid(3),
+        vout,
+      },
+      offset: 0,
+    }
+  }
+
+  #[test]
+  fn transfer_inscribed_to_another_address_is_sent_by_reveal() {
+    let raw_tx_result = reveal_tx_result_with_second_output(&owner(2));
+
+    let (spender, send_tx, sat_point) = to_reveal_send(
+      &raw_tx_result,
+      reveal_satpoint(1),
+      &owner(1),
+      Network::Bitcoin,
+    )
+    .unwrap();
+
+    assert_eq!(spender, owner(1));
+    assert_eq!(send_tx.get_txid(), txid(3));
+    assert_eq!(send_tx.get_vout(), 1);
+    assert_eq!(send_tx.get_owner(), &owner(2));
+    assert_eq!(sat_point, reveal_satpoint(1));
+  }
+
+  #[test]
+  fn moved_inscription_is_owned_by_the_reveal_output_it_landed_in() {
+    let mut spend_tx_result = raw_tx_result(7, &owner(3), 400);
+    spend_tx_result.vin = vec![GetRawTransactionResultVin {
+      sequence: 0,
+      coinbase: None,
+      txid: Some(txid(3)),
+      vout: Some(1),
+      script_sig: None,
+      txinwitness: None,
+    }];
+
+    let tx_provider = LocalTxProvider::new([
+      reveal_tx_result_with_second_output(&owner(2)),
+      spend_tx_result,
+    ]);
+    let tx_info_cache = TxInfoCache::new(10);
+
+    let satpoint = get_reveal_satpoint(
+      &tx_provider,
+      &tx_info_cache,
+      inscription_id(3),
+      sat_point(7),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(satpoint, reveal_satpoint(1));
+
+    let brc20_tx = get_brc20_tx(
+      &tx_provider,
+      &tx_info_cache,
+      Network::Bitcoin,
+      inscription_id(3),
+      Some(satpoint.outpoint.vout),
+    )
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(brc20_tx.get_vout(), 1);
+    assert_eq!(brc20_tx.get_owner(), &owner(2));
+  }
+
+  #[test]
+  fn transfer_inscribed_to_inscriber_is_not_sent_by_reveal() {
+    let raw_tx_result = reveal_tx_result_with_second_output(&owner(1));
+
+    assert_eq!(
+      to_reveal_send(
+        &raw_tx_result,
+        reveal_satpoint(0),
+        &owner(1),
+        Network::Bitcoin
+      ),
+      None
+    );
+    assert_eq!(
+      to_reveal_send(
+        &raw_tx_result,
+        reveal_satpoint(1),
+        &owner(1),
+        Network::Bitcoin
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn transfer_inscribed_and_sent_in_one_transaction_completes() {
+    let mut brc20_index = Brc20Index::new();
+
+    assert!(brc20_index.process_deploy(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000"}"#).unwrap(),
+    ));
+    assert!(brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(1), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    ));
+
+    let raw_tx_result = reveal_tx_result_with_second_output(&owner(2));
+    let brc20_tx = Brc20Tx::new(&raw_tx_result, Network::Bitcoin).unwrap();
+
+    let mut transfer_sends = TransferSends::default();
+
+    apply(
+      &mut brc20_index,
+      &mut transfer_sends,
+      Fetched {
+        inscription_id: inscription_id(3),
+        inscription_number: 3,
+        height: 3,
+        operations: Brc20Operation::parse_all(
+          r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"40"}"#,
+        ),
+        transfer_send: to_reveal_send(
+          &raw_tx_result,
+          reveal_satpoint(1),
+          brc20_tx.get_owner(),
+          Network::Bitcoin,
+        )
+        .map(|(spender, send_tx, sat_point)| {
+          (
+            spender,
+            send_tx,
+            sat_point,
+            TxPosition {
+              height: 3,
+              index: 1,
+            },
+          )
+        }),
+        genesis: Genesis::new(55, &raw_tx_result, None),
+        brc20_tx,
+        sat_point: reveal_satpoint(1),
+      },
+    );
+
+    transfer_sends.apply(&mut brc20_index);
+
+    let ticker = brc20_index.get_ticker("ordi").unwrap();
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(1))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("60", 18)
+    );
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("40", 18)
+    );
+
+    let transfer_tx = &ticker.get_transfers()[0];
+    assert!(transfer_tx.is_completed());
+    assert_eq!(transfer_tx.get_receiver(), Some(&owner(2)));
+  }
+}
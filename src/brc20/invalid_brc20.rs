@@ -0,0 +1,77 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct InvalidBrc20Tx {
+  inscription_id: InscriptionId,
+  op: &'static str,
+  tick: String,
+  brc20_tx: Brc20Tx,
+  reason: String,
+}
+
+impl InvalidBrc20Tx {
+  pub(crate) fn new(
+    inscription_id: InscriptionId,
+    op: &'static str,
+    tick: String,
+    brc20_tx: Brc20Tx,
+    reason: String,
+  ) -> Self {
+    Self {
+      inscription_id,
+      op,
+      tick,
+      brc20_tx,
+      reason,
+    }
+  }
+
+  pub(crate) fn get_inscription_id(&self) -> InscriptionId {
+    self.inscription_id
+  }
+
+  /// The operation that was rejected: `deploy`, `mint`, `transfer`, or
+  /// `send`.
+  pub(crate) fn get_op(&self) -> &'static str {
+    self.op
+  }
+
+  /// The ticker as written in the operation.
+  pub(crate) fn get_tick(&self) -> &str {
+    &self.tick
+  }
+
+  pub(crate) fn get_brc20_tx(&self) -> &Brc20Tx {
+    &self.brc20_tx
+  }
+
+  pub(crate) fn get_reason(&self) -> &str {
+    &self.reason
+  }
+}
+
+/// Invalid transactions by inscription. An inscription holding an array of
+/// operations can have more than one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct InvalidBrc20TxMap {
+  map: HashMap<InscriptionId, Vec<InvalidBrc20Tx>>,
+}
+
+impl InvalidBrc20TxMap {
+  pub(crate) fn add_invalid_tx(&mut self, invalid_tx: InvalidBrc20Tx) {
+    self
+      .map
+      .entry(invalid_tx.get_inscription_id())
+      .or_default()
+      .push(invalid_tx);
+  }
+
+  /// Invalid transactions in no particular order.
+  pub(crate) fn iter(&self) -> impl Iterator<Item = &InvalidBrc20Tx> {
+    self.map.values().flatten()
+  }
+
+  pub(crate) fn len(&self) -> usize {
+    self.map.values().map(Vec::len).sum()
+  }
+}
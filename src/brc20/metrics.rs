@@ -0,0 +1,155 @@
+use {super::*, std::sync::atomic::AtomicU64};
+
+/// Counters and gauges of BRC-20 indexing, rendered in the Prometheus text
+/// exposition format. Counters accumulate across rebuilds, gauges describe
+/// the last completed one.
+#[derive(Debug, Default)]
+pub(crate) struct Metrics {
+  inscriptions_processed: AtomicU64,
+  height: AtomicU64,
+  last_successful_height: AtomicU64,
+  deploys: AtomicU64,
+  mints: AtomicU64,
+  transfers: AtomicU64,
+  invalid: AtomicU64,
+}
+
+impl Metrics {
+  pub(crate) fn inscription_processed(&self) {
+    self
+      .inscriptions_processed
+      .fetch_add(1, atomic::Ordering::Relaxed);
+  }
+
+  /// Sets the height of the ord index being indexed.
+  pub(crate) fn indexing(&self, height: u64) {
+    self.height.store(height, atomic::Ordering::Relaxed);
+  }
+
+  /// Records what `brc20_index`, indexed up to `height`, holds.
+  pub(crate) fn indexed(&self, brc20_index: &Brc20Index, height: u64) {
+    let (mints, transfers) = brc20_index
+      .tickers()
+      .fold((0, 0), |(mints, transfers), ticker| {
+        (
+          mints + ticker.get_mints().len(),
+          transfers + ticker.get_transfers().len(),
+        )
+      });
+
+    for (gauge, value) in [
+      (&self.deploys, brc20_index.ticker_count()),
+      (&self.mints, mints),
+      (&self.transfers, transfers),
+      (&self.invalid, brc20_index.get_invalid_tx_map().len()),
+    ] {
+      gauge.store(value.try_into().unwrap(), atomic::Ordering::Relaxed);
+    }
+
+    self
+      .last_successful_height
+      .store(height, atomic::Ordering::Relaxed);
+  }
+
+  pub(crate) fn render(&self) -> String {
+    let mut rendered = String::new();
+
+    for (name, kind, help, value) in [
+      (
+        "brc20_inscriptions_processed_total",
+        "counter",
+        "Inscriptions processed by BRC-20 indexing.",
+        &self.inscriptions_processed,
+      ),
+      (
+        "brc20_height",
+        "gauge",
+        "Height of the ord index being indexed.",
+        &self.height,
+      ),
+      (
+        "brc20_last_successful_height",
+        "gauge",
+        "Height of the ord index the served BRC-20 index was built from.",
+        &self.last_successful_height,
+      ),
+      (
+        "brc20_deploys",
+        "gauge",
+        "Valid deploys in the served BRC-20 index.",
+        &self.deploys,
+      ),
+      (
+        "brc20_mints",
+        "gauge",
+        "Valid mints in the served BRC-20 index.",
+        &self.mints,
+      ),
+      (
+        "brc20_transfers",
+        "gauge",
+        "Valid transfers in the served BRC-20 index.",
+        &self.transfers,
+      ),
+      (
+        "brc20_invalid",
+        "gauge",
+        "Rejected operations in the served BRC-20 index.",
+        &self.invalid,
+      ),
+    ] {
+      rendered.push_str(&format!(
+        "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {}\n",
+        value.load(atomic::Ordering::Relaxed)
+      ));
+    }
+
+    rendered
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn render() {
+    let metrics = Metrics::default();
+
+    metrics.inscription_processed();
+    metrics.inscription_processed();
+    metrics.indexing(7);
+
+    let rendered = metrics.render();
+
+    assert!(rendered.contains(
+      "# HELP brc20_inscriptions_processed_total Inscriptions processed by BRC-20 indexing.\n\
+       # TYPE brc20_inscriptions_processed_total counter\n\
+       brc20_inscriptions_processed_total 2\n"
+    ));
+    assert!(rendered.contains("# TYPE brc20_height gauge\nbrc20_height 7\n"));
+    assert!(rendered.contains("brc20_last_successful_height 0\n"));
+  }
+
+  #[test]
+  fn indexed() {
+    let mut brc20_index = Brc20Index::new();
+
+    brc20_index.process_deploy(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000"}"#).unwrap(),
+    );
+
+    let metrics = Metrics::default();
+
+    metrics.indexed(&brc20_index, 5);
+
+    let rendered = metrics.render();
+
+    assert!(rendered.contains("\nbrc20_deploys 1\n"));
+    assert!(rendered.contains("\nbrc20_mints 0\n"));
+    assert!(rendered.contains("\nbrc20_last_successful_height 5\n"));
+  }
+}
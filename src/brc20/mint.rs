@@ -0,0 +1,501 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Brc20Mint {
+  pub(crate) p: String,
+  pub(crate) op: String,
+  pub(crate) tick: String,
+  pub(crate) amt: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Brc20MintTx {
+  inscription_id: InscriptionId,
+  inscription_number: i64,
+  brc20_tx: Brc20Tx,
+  mint_script: Brc20Mint,
+  /// In base units.
+  #[serde(serialize_with = "serialize_base_units")]
+  amount: u128,
+  decimals: u8,
+}
+
+impl Brc20MintTx {
+  pub(crate) fn new(
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    mint_script: Brc20Mint,
+  ) -> Self {
+    Self {
+      inscription_id,
+      inscription_number,
+      brc20_tx,
+      mint_script,
+      amount: 0,
+      decimals: 18,
+    }
+  }
+
+  pub(crate) fn validate(
+    mut self,
+    tickers: &HashMap<String, Brc20Ticker>,
+  ) -> Result<Self, Brc20Error> {
+    let ticker = tickers
+      .get(&self.get_ticker())
+      .ok_or_else(|| Brc20Error::TickerNotFound(self.mint_script.tick.clone()))?;
+
+    let deploy_tx = ticker.get_deploy_tx();
+
+    // A mint in the deploy's own block is fine, since it's ordered after the
+    // deploy within the block.
+    if self.brc20_tx.get_blocktime() < deploy_tx.get_brc20_tx().get_blocktime() {
+      return Err(Brc20Error::MintPrecedesDeploy);
+    }
+
+    if deploy_tx.is_self_mint() && self.brc20_tx.get_owner() != deploy_tx.get_brc20_tx().get_owner()
+    {
+      return Err(Brc20Error::UnauthorizedSelfMint(self.mint_script.tick));
+    }
+
+    if ticker.is_fully_minted() {
+      return Err(Brc20Error::TickerFullyMinted);
+    }
+
+    self.decimals = ticker.get_decimals();
+    self.amount = convert_to_base_units(&self.mint_script.amt, self.decimals)?;
+
+    if self.amount == 0 {
+      return Err(Brc20Error::InvalidAmount);
+    }
+
+    // The requested amount is checked against the limit before it's clamped
+    // to the remaining supply, so a final mint can't exceed the limit either.
+    if self.amount > ticker.get_limit() {
+      return Err(Brc20Error::MintAmountExceedsLimit);
+    }
+
+    let remaining = ticker
+      .get_max_supply()
+      .saturating_sub(ticker.get_total_minted());
+
+    // Mints are validated one at a time against the running total, so a mint
+    // that lost the last tokens to an earlier one in the same block is
+    // rejected instead of clamped to nothing.
+    if remaining == 0 {
+      return Err(Brc20Error::TickerFullyMinted);
+    }
+
+    self.amount = self.amount.min(remaining);
+
+    Ok(self)
+  }
+
+  pub(crate) fn get_inscription_id(&self) -> InscriptionId {
+    self.inscription_id
+  }
+
+  pub(crate) fn get_inscription_number(&self) -> i64 {
+    self.inscription_number
+  }
+
+  pub(crate) fn get_brc20_tx(&self) -> &Brc20Tx {
+    &self.brc20_tx
+  }
+
+  pub(crate) fn get_ticker(&self) -> String {
+    self.mint_script.tick.to_lowercase()
+  }
+
+  pub(crate) fn get_mint_script(&self) -> &Brc20Mint {
+    &self.mint_script
+  }
+
+  pub(crate) fn get_amount(&self) -> u128 {
+    self.amount
+  }
+}
+
+impl Display for Brc20MintTx {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "Mint of {} {} to {} in {}",
+      format_base_units(self.amount, self.decimals),
+      self.mint_script.tick,
+      self.brc20_tx.get_owner(),
+      self.brc20_tx.get_txid(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_mint() {
+    assert_eq!(
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#),
+      Some(Brc20Mint {
+        p: "brc-20".into(),
+        op: "mint".into(),
+        tick: "ordi".into(),
+        amt: "1000".into(),
+      })
+    );
+  }
+
+  #[test]
+  fn mint_with_unknown_field_is_rejected() {
+    assert_eq!(
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000","max":"1"}"#),
+      None
+    );
+  }
+
+  #[test]
+  fn mint_without_amount_is_rejected() {
+    assert_eq!(
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi"}"#),
+      None
+    );
+  }
+
+  fn tickers(body: &str) -> HashMap<String, Brc20Ticker> {
+    let deploy_tx = Brc20DeployTx::new(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(body).unwrap(),
+      DEFAULT_DECIMALS,
+    )
+    .validate_deploy_script(&HashMap::new(), &TickerLengthPolicy::default())
+    .unwrap();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+    tickers
+  }
+
+  fn mint(n: u32, amt: &str) -> Brc20MintTx {
+    Brc20MintTx::new(
+      inscription_id(n),
+      n.into(),
+      brc20_tx(n.into(), &owner(2), 200),
+      mint_script(&format!(
+        r#"{{"p":"brc-20","op":"mint","tick":"ordi","amt":"{amt}"}}"#
+      ))
+      .unwrap(),
+    )
+  }
+
+  #[test]
+  fn valid_mint() {
+    let tickers = tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"10"}"#);
+    let mint_tx = mint(2, "10").validate(&tickers).unwrap();
+    assert_eq!(mint_tx.get_amount(), base_units("10", 18));
+    assert_eq!(mint_tx.get_ticker(), "ordi");
+  }
+
+  #[test]
+  fn mint_of_unknown_ticker_is_invalid() {
+    assert_eq!(
+      mint(2, "10").validate(&HashMap::new()),
+      Err(Brc20Error::TickerNotFound("ordi".into()))
+    );
+  }
+
+  #[test]
+  fn self_mint_ticker_can_only_be_minted_by_deployer() {
+    let tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordix","max":"1000","self_mint":"true"}"#);
+
+    let self_mint = |n: u32, minter: &Owner| {
+      Brc20MintTx::new(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), minter, 200),
+        mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordix","amt":"10"}"#).unwrap(),
+      )
+      .validate(&tickers)
+    };
+
+    assert_eq!(
+      self_mint(2, &owner(1)).unwrap().get_amount(),
+      base_units("10", 18)
+    );
+
+    let err = self_mint(3, &owner(2)).unwrap_err();
+    assert_eq!(err, Brc20Error::UnauthorizedSelfMint("ordix".into()));
+    assert_eq!(
+      err.to_string(),
+      "Only the deployer can mint self-mint ticker: ordix"
+    );
+  }
+
+  #[test]
+  fn mint_before_deploy_blocktime_is_invalid() {
+    let tickers = tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"10"}"#);
+
+    let mint_at = |n: u32, blocktime| {
+      Brc20MintTx::new(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(2), blocktime),
+        mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"10"}"#).unwrap(),
+      )
+      .validate(&tickers)
+    };
+
+    let err = mint_at(2, 99).unwrap_err();
+    assert_eq!(err, Brc20Error::MintPrecedesDeploy);
+    assert_eq!(err.to_string(), "Mint precedes deploy");
+
+    assert_eq!(mint_at(3, 100).unwrap().get_amount(), base_units("10", 18));
+  }
+
+  #[test]
+  fn mint_above_limit_is_invalid() {
+    let tickers = tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"10"}"#);
+    assert_eq!(
+      mint(2, "11").validate(&tickers),
+      Err(Brc20Error::MintAmountExceedsLimit)
+    );
+  }
+
+  #[test]
+  fn mint_just_over_limit_is_invalid() {
+    let tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#);
+
+    assert_eq!(
+      mint(2, "1000.000000000001").validate(&tickers),
+      Err(Brc20Error::MintAmountExceedsLimit)
+    );
+    assert_eq!(
+      mint(3, "1000.000000000000000001").validate(&tickers),
+      Err(Brc20Error::MintAmountExceedsLimit)
+    );
+    assert_eq!(
+      mint(4, "1000").validate(&tickers).unwrap().get_amount(),
+      base_units("1000", 18)
+    );
+  }
+
+  #[test]
+  fn fractional_limit_of_18_decimal_token_is_enforced() {
+    let tickers = tickers(
+      r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"0.000000000000000005","dec":"18"}"#,
+    );
+
+    assert_eq!(
+      mint(2, "0.000000000000000005")
+        .validate(&tickers)
+        .unwrap()
+        .get_amount(),
+      base_units("0.000000000000000005", 18)
+    );
+    assert_eq!(
+      mint(3, "0.000000000000000006").validate(&tickers),
+      Err(Brc20Error::MintAmountExceedsLimit)
+    );
+  }
+
+  #[test]
+  fn mint_with_empty_amount_is_invalid() {
+    let tickers = tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"10"}"#);
+    assert_eq!(
+      mint(2, "").validate(&tickers),
+      Err(Brc20Error::MissingNumber)
+    );
+  }
+
+  #[test]
+  fn mint_of_zero_is_invalid() {
+    let tickers = tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"10"}"#);
+    assert_eq!(
+      mint(2, "0").validate(&tickers),
+      Err(Brc20Error::InvalidAmount)
+    );
+  }
+
+  #[test]
+  fn final_mint_is_clamped_to_remaining_supply() {
+    let mut tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"15","lim":"10"}"#);
+
+    let mint_tx = mint(2, "10").validate(&tickers).unwrap();
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    assert_eq!(
+      mint(3, "10").validate(&tickers).unwrap().get_amount(),
+      base_units("5", 18)
+    );
+  }
+
+  #[test]
+  fn final_mint_above_limit_is_invalid() {
+    let mut tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"15","lim":"10"}"#);
+
+    let mint_tx = mint(2, "10").validate(&tickers).unwrap();
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    assert_eq!(
+      mint(3, "11").validate(&tickers),
+      Err(Brc20Error::MintAmountExceedsLimit)
+    );
+  }
+
+  #[test]
+  fn mint_reaching_max_supply_is_checked_against_pre_mint_total() {
+    let mut tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"20","lim":"10"}"#);
+
+    let mint_tx = mint(2, "10").validate(&tickers).unwrap();
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    let mint_tx = mint(3, "10").validate(&tickers).unwrap();
+    assert_eq!(mint_tx.get_amount(), base_units("10", 18));
+
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+    assert_eq!(tickers["ordi"].get_total_minted(), base_units("20", 18));
+  }
+
+  #[test]
+  fn sats_scale_mints_are_added_exactly() {
+    let mut tickers = tickers(
+      r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"2100000000000000","lim":"100000000"}"#,
+    );
+
+    for (n, amt) in [(2, "100000000"), (3, "99999999.999999999999999999")] {
+      let mint_tx = Brc20MintTx::new(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(2), 200),
+        mint_script(&format!(
+          r#"{{"p":"brc-20","op":"mint","tick":"sats","amt":"{amt}"}}"#
+        ))
+        .unwrap(),
+      )
+      .validate(&tickers)
+      .unwrap();
+      tickers.get_mut("sats").unwrap().add_mint(mint_tx);
+    }
+
+    assert_eq!(
+      tickers["sats"].get_total_minted(),
+      199_999_999_999_999_999_999_999_999
+    );
+  }
+
+  #[test]
+  fn sats_scale_ticker_is_not_fully_minted_until_the_last_base_unit() {
+    let mut tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"2100000000000000"}"#);
+
+    let sats_mint = |n: u32, amt: &str| {
+      Brc20MintTx::new(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(2), 200),
+        mint_script(&format!(
+          r#"{{"p":"brc-20","op":"mint","tick":"sats","amt":"{amt}"}}"#
+        ))
+        .unwrap(),
+      )
+    };
+
+    let mint_tx = sats_mint(2, "2099999999999999.79")
+      .validate(&tickers)
+      .unwrap();
+    tickers.get_mut("sats").unwrap().add_mint(mint_tx);
+
+    assert!(!tickers["sats"].is_fully_minted());
+
+    let mint_tx = sats_mint(3, "1").validate(&tickers).unwrap();
+    assert_eq!(mint_tx.get_amount(), 210_000_000_000_000_000);
+    tickers.get_mut("sats").unwrap().add_mint(mint_tx);
+
+    assert!(tickers["sats"].is_fully_minted());
+  }
+
+  #[test]
+  fn mint_after_max_supply_is_invalid() {
+    let mut tickers = tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"10"}"#);
+
+    let mint_tx = mint(2, "10").validate(&tickers).unwrap();
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    assert!(tickers["ordi"].is_fully_minted());
+
+    let err = mint(3, "1").validate(&tickers).unwrap_err();
+    assert_eq!(err, Brc20Error::TickerFullyMinted);
+    assert_eq!(err.to_string(), "Ticker fully minted");
+  }
+
+  #[test]
+  fn limit_equal_to_max_supply_allows_a_single_full_mint() {
+    let mut tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","lim":"1000"}"#);
+
+    let mint_tx = mint(2, "1000").validate(&tickers).unwrap();
+    assert_eq!(mint_tx.get_amount(), base_units("1000", 18));
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    assert!(tickers["ordi"].is_fully_minted());
+    assert_eq!(
+      mint(3, "1000").validate(&tickers),
+      Err(Brc20Error::TickerFullyMinted)
+    );
+  }
+
+  #[test]
+  fn clamped_final_mint_fully_mints_ticker() {
+    let mut tickers =
+      tickers(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"15","lim":"10"}"#);
+
+    for n in [2, 3] {
+      assert!(!tickers["ordi"].is_fully_minted());
+      let mint_tx = mint(n, "10").validate(&tickers).unwrap();
+      tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+    }
+
+    assert!(tickers["ordi"].is_fully_minted());
+    assert_eq!(
+      mint(4, "10").validate(&tickers),
+      Err(Brc20Error::TickerFullyMinted)
+    );
+  }
+
+  #[test]
+  fn display_formats_amount_with_decimals() {
+    let mint_tx = |amount, decimals| Brc20MintTx {
+      inscription_id: inscription_id(1),
+      inscription_number: 1,
+      brc20_tx: brc20_tx(1, &owner(1), 100),
+      mint_script: mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}"#).unwrap(),
+      amount,
+      decimals,
+    };
+
+    assert_eq!(
+      mint_tx(1_000_000_000_000_000_000, 18).to_string(),
+      format!("Mint of 1.0 ordi to {} in {}", owner(1), txid(1))
+    );
+    assert_eq!(
+      mint_tx(1000, 0).to_string(),
+      format!("Mint of 1000 ordi to {} in {}", owner(1), txid(1))
+    );
+  }
+
+  #[test]
+  fn transfer_is_not_parsed_as_mint() {
+    assert_eq!(
+      mint_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"1000"}"#),
+      None
+    );
+  }
+}
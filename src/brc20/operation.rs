@@ -0,0 +1,200 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Brc20Operation {
+  Deploy(Brc20Deploy),
+  Mint(Brc20Mint),
+  Transfer(Brc20Transfer),
+}
+
+impl Brc20Operation {
+  /// Parses `body` as either a single operation or a top-level array of
+  /// operations, in order. Each is parsed once, reading `op` to pick the
+  /// script type to deserialize. `op` is matched case-insensitively,
+  /// ignoring surrounding whitespace. Array elements that aren't operations
+  /// at all are skipped. Transfers and any mint of a ticker already minted
+  /// earlier in the array are returned as errors, along with the operation,
+  /// so they can be recorded as invalid: a transfer is bound to the sat of
+  /// the inscription carrying it, and one inscription may mint a ticker at
+  /// most once. The raw body of a deploy in an array is its element
+  /// re-serialized, since the element's original text isn't kept.
+  pub(crate) fn parse_all(body: &str) -> Vec<Result<Self, (Self, Brc20Error)>> {
+    match serde_json::from_str::<serde_json::Value>(body) {
+      Ok(serde_json::Value::Array(values)) => {
+        let mut minted = HashSet::new();
+
+        values
+          .into_iter()
+          .filter_map(|value| {
+            let raw = value.to_string();
+            Self::from_value(value, &raw)
+          })
+          .map(|operation| match &operation {
+            Self::Transfer(_) => Err((operation, Brc20Error::TransferInArray)),
+            Self::Mint(mint_script) if !minted.insert(mint_script.tick.to_lowercase()) => {
+              let tick = mint_script.tick.clone();
+              Err((operation, Brc20Error::RepeatedMintInArray(tick)))
+            }
+            _ => Ok(operation),
+          })
+          .collect()
+      }
+      Ok(value) => Self::from_value(value, body).map(Ok).into_iter().collect(),
+      Err(_) => Vec::new(),
+    }
+  }
+
+  fn from_value(value: serde_json::Value, raw: &str) -> Option<Self> {
+    match value.get("op")?.as_str()?.trim().to_lowercase().as_str() {
+      "deploy" => serde_json::from_value(value).ok().map(|deploy_script| {
+        Self::Deploy(Brc20Deploy {
+          raw: raw.into(),
+          ..deploy_script
+        })
+      }),
+      "mint" => serde_json::from_value(value).ok().map(Self::Mint),
+      "transfer" => serde_json::from_value(value).ok().map(Self::Transfer),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn op(&self) -> &'static str {
+    match self {
+      Self::Deploy(_) => "deploy",
+      Self::Mint(_) => "mint",
+      Self::Transfer(_) => "transfer",
+    }
+  }
+
+  pub(crate) fn tick(&self) -> &str {
+    match self {
+      Self::Deploy(deploy_script) => &deploy_script.tick,
+      Self::Mint(mint_script) => &mint_script.tick,
+      Self::Transfer(transfer_script) => &transfer_script.tick,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(body: &str) -> Option<Brc20Operation> {
+    match Brc20Operation::parse_all(body).as_slice() {
+      [Ok(operation)] => Some(operation.clone()),
+      [] => None,
+      operations => panic!("unexpected operations: {operations:?}"),
+    }
+  }
+
+  #[test]
+  fn parse_deploy() {
+    assert!(matches!(
+      parse(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#),
+      Some(Brc20Operation::Deploy(Brc20Deploy { tick, .. })) if tick == "ordi"
+    ));
+  }
+
+  #[test]
+  fn parse_mint() {
+    assert!(matches!(
+      parse(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#),
+      Some(Brc20Operation::Mint(Brc20Mint { amt, .. })) if amt == "1000"
+    ));
+  }
+
+  #[test]
+  fn parse_transfer() {
+    assert!(matches!(
+      parse(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"10"}"#),
+      Some(Brc20Operation::Transfer(Brc20Transfer { amt, .. })) if amt == "10"
+    ));
+  }
+
+  #[test]
+  fn parse_op_ignores_case_and_whitespace() {
+    assert!(matches!(
+      parse(r#"{"p":"brc-20","op":" MINT ","tick":"ordi","amt":"1000"}"#),
+      Some(Brc20Operation::Mint(_))
+    ));
+  }
+
+  #[test]
+  fn parse_invalid() {
+    for body in [
+      "",
+      "not json",
+      "[]",
+      r#"{"p":"brc-20","tick":"ordi","amt":"10"}"#,
+      r#"{"p":"brc-20","op":7,"tick":"ordi","amt":"10"}"#,
+      r#"{"p":"brc-20","op":"burn","tick":"ordi","amt":"10"}"#,
+      r#"{"p":"brc-20","op":"mint","tick":"ordi","max":"10"}"#,
+      r#"{"p":"brc-20","op":"deploy","tick":"ordi","amt":"10"}"#,
+    ] {
+      assert_eq!(parse(body), None, "{body}");
+    }
+  }
+  #[test]
+  fn parse_all_single() {
+    assert!(matches!(
+      Brc20Operation::parse_all(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}"#).as_slice(),
+      [Ok(Brc20Operation::Mint(Brc20Mint { amt, .. }))] if amt == "1"
+    ));
+  }
+
+  #[test]
+  fn parse_all_array() {
+    let operations = Brc20Operation::parse_all(
+      r#"[
+        {"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"},
+        {"p":"brc-20","op":"burn","tick":"ordi","amt":"1"},
+        {"p":"brc-20","op":"transfer","tick":"ordi","amt":"1"},
+        {"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}
+      ]"#,
+    );
+
+    assert_eq!(operations.len(), 3);
+    assert!(matches!(operations[0], Ok(Brc20Operation::Deploy(_))));
+    assert!(matches!(
+      operations[1],
+      Err((Brc20Operation::Transfer(_), Brc20Error::TransferInArray))
+    ));
+    assert!(matches!(operations[2], Ok(Brc20Operation::Mint(_))));
+  }
+
+  #[test]
+  fn parse_all_rejects_repeated_mints_of_a_ticker() {
+    let operations = Brc20Operation::parse_all(
+      r#"[
+        {"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"},
+        {"p":"brc-20","op":"mint","tick":"pepe","amt":"1000"},
+        {"p":"brc-20","op":"mint","tick":"ORDI","amt":"1000"}
+      ]"#,
+    );
+
+    assert_eq!(operations.len(), 3);
+    assert!(matches!(operations[0], Ok(Brc20Operation::Mint(_))));
+    assert!(matches!(operations[1], Ok(Brc20Operation::Mint(_))));
+    assert!(matches!(
+      &operations[2],
+      Err((Brc20Operation::Mint(_), Brc20Error::RepeatedMintInArray(tick))) if tick == "ORDI"
+    ));
+  }
+
+  #[test]
+  fn parse_all_invalid() {
+    for body in ["", "not json", "[]", "7", r#"{"p":"brc-20","op":"burn"}"#] {
+      assert_eq!(Brc20Operation::parse_all(body), [], "{body}");
+    }
+  }
+
+  #[test]
+  fn tick() {
+    assert_eq!(
+      parse(r#"{"p":"brc-20","op":"mint","tick":"OrDi","amt":"1"}"#)
+        .unwrap()
+        .tick(),
+      "OrDi"
+    );
+  }
+}
@@ -0,0 +1,126 @@
+use super::*;
+
+/// The holder of a BRC-20 balance: the address of an output, or, for
+/// outputs whose script has no standard address, such as bare multisig or
+/// pay-to-pubkey, the script itself. Scripts are shown as hex.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Owner {
+  Address(Address),
+  Script(Script),
+}
+
+impl Owner {
+  pub(crate) fn from_script(script: &Script, network: Network) -> Self {
+    match Address::from_script(script, network) {
+      Ok(address) => Self::Address(address),
+      Err(_) => Self::Script(script.clone()),
+    }
+  }
+
+  /// Scripts aren't tied to a network, so only addresses can be invalid.
+  pub(crate) fn is_valid_for_network(&self, network: Network) -> bool {
+    match self {
+      Self::Address(address) => address.is_valid_for_network(network),
+      Self::Script(_) => true,
+    }
+  }
+}
+
+impl Display for Owner {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Address(address) => write!(f, "{address}"),
+      Self::Script(script) => write!(f, "{script:x}"),
+    }
+  }
+}
+
+impl FromStr for Owner {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if let Ok(address) = s.parse() {
+      return Ok(Self::Address(address));
+    }
+
+    Ok(Self::Script(s.parse().map_err(|_| {
+      anyhow!("`{s}` is neither an address nor a hex script")
+    })?))
+  }
+}
+
+impl From<Address> for Owner {
+  fn from(address: Address) -> Self {
+    Self::Address(address)
+  }
+}
+
+impl Serialize for Owner {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
+impl<'de> Deserialize<'de> for Owner {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    Ok(DeserializeFromStr::deserialize(deserializer)?.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A bare 1-of-1 multisig script, which has no address.
+  fn bare_multisig() -> Script {
+    "5121020202020202020202020202020202020202020202020202020202020202020251ae"
+      .parse()
+      .unwrap()
+  }
+
+  #[test]
+  fn scripts_with_an_address_are_owned_by_it() {
+    assert_eq!(
+      Owner::from_script(&script_pubkey(&owner(1)), Network::Bitcoin),
+      owner(1)
+    );
+  }
+
+  #[test]
+  fn scripts_without_an_address_are_their_own_owner() {
+    let owner = Owner::from_script(&bare_multisig(), Network::Bitcoin);
+
+    assert_eq!(owner, Owner::Script(bare_multisig()));
+    assert!(owner.is_valid_for_network(Network::Testnet));
+  }
+
+  #[test]
+  fn round_trips_through_strings() {
+    for owner in [owner(1), Owner::Script(bare_multisig())] {
+      assert_eq!(owner.to_string().parse::<Owner>().unwrap(), owner);
+      assert_eq!(
+        serde_json::from_str::<Owner>(&serde_json::to_string(&owner).unwrap()).unwrap(),
+        owner
+      );
+    }
+
+    assert_eq!(
+      Owner::Script(bare_multisig()).to_string(),
+      "5121020202020202020202020202020202020202020202020202020202020202020251ae"
+    );
+  }
+
+  #[test]
+  fn strings_that_are_neither_addresses_nor_hex_are_rejected() {
+    assert_eq!(
+      "foo".parse::<Owner>().unwrap_err().to_string(),
+      "`foo` is neither an address nor a hex script"
+    );
+  }
+}
@@ -0,0 +1,36 @@
+use super::*;
+
+/// Values of `p` accepted as BRC-20. Values are compared after trimming
+/// whitespace and lowercasing, like `op`, so only spelling variants such as
+/// `brc20` need to be listed. Only `brc-20` is accepted by default.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProtocolAllowlist(BTreeSet<String>);
+
+impl ProtocolAllowlist {
+  pub(crate) fn new<P: AsRef<str>>(protocols: impl IntoIterator<Item = P>) -> Self {
+    Self(
+      protocols
+        .into_iter()
+        .map(|protocol| Self::normalize(protocol.as_ref()))
+        .collect(),
+    )
+  }
+
+  pub(crate) fn check(&self, p: &str) -> Result<(), Brc20Error> {
+    if self.0.contains(&Self::normalize(p)) {
+      Ok(())
+    } else {
+      Err(Brc20Error::InvalidProtocol(p.into()))
+    }
+  }
+
+  fn normalize(p: &str) -> String {
+    p.trim().to_lowercase()
+  }
+}
+
+impl Default for ProtocolAllowlist {
+  fn default() -> Self {
+    Self::new([PROTOCOL])
+  }
+}
@@ -0,0 +1,699 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Brc20TickerStats {
+  pub(crate) holders: usize,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) total_minted: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) circulating_supply: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) transferred: u128,
+  pub(crate) active_transfers: usize,
+  pub(crate) mint_time_range: Option<(u64, u64)>,
+  pub(crate) holders_by_address_type: BTreeMap<AddressType, usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TickerSummary {
+  pub(crate) ticker: String,
+  pub(crate) decimals: u8,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) max_supply: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) total_minted: u128,
+  pub(crate) percent_minted: f64,
+  pub(crate) holders: usize,
+  pub(crate) deploy_blocktime: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Brc20Ticker {
+  tick: String,
+  display_tick: String,
+  /// Limit, max supply, total minted, and transfer volume are in base units.
+  #[serde(serialize_with = "serialize_base_units")]
+  limit: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  max_supply: u128,
+  decimals: u8,
+  #[serde(serialize_with = "serialize_base_units")]
+  total_minted: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  transfer_volume: u128,
+  deploy_tx: Brc20DeployTx,
+  mints: Vec<Brc20MintTx>,
+  transfers: Vec<Brc20TransferTx>,
+  balances: HashMap<Owner, UserBalance>,
+}
+
+impl Brc20Ticker {
+  pub(crate) fn new(deploy_tx: Brc20DeployTx) -> Self {
+    Self {
+      tick: deploy_tx.get_ticker(),
+      display_tick: deploy_tx.get_display_ticker().into(),
+      limit: deploy_tx.get_limit(),
+      max_supply: deploy_tx.get_max_supply(),
+      decimals: deploy_tx.get_decimals(),
+      total_minted: 0,
+      transfer_volume: 0,
+      deploy_tx,
+      mints: Vec::new(),
+      transfers: Vec::new(),
+      balances: HashMap::new(),
+    }
+  }
+
+  pub(crate) fn add_mint(&mut self, mint_tx: Brc20MintTx) {
+    self
+      .balances
+      .entry(mint_tx.get_brc20_tx().get_owner().clone())
+      .or_insert_with(|| UserBalance::new(self.decimals))
+      .add_mint(mint_tx.clone());
+
+    self.total_minted += mint_tx.get_amount();
+    self.mints.push(mint_tx);
+  }
+
+  pub(crate) fn add_transfer(&mut self, transfer_tx: Brc20TransferTx) {
+    self.transfers.push(transfer_tx);
+  }
+
+  pub(crate) fn handle_transfer_send(
+    &mut self,
+    inscription_id: InscriptionId,
+    sender: &Owner,
+    spender: &Owner,
+    send_tx: Brc20Tx,
+    sat_point: SatPoint,
+  ) -> Result<Option<Brc20TransferTx>, Brc20Error> {
+    let Some(sender_balance) = self
+      .balances
+      .get_mut(sender)
+      .filter(|sender_balance| {
+        sender_balance
+          .get_active_transfer_inscriptions()
+          .contains_key(&inscription_id)
+      })
+    else {
+      log::warn!("transfer inscription {inscription_id} is not active, skipping send");
+      return Ok(None);
+    };
+
+    if spender != sender {
+      return Err(Brc20Error::TransferSpentByNonOwner);
+    }
+
+    let Some(mut transfer_tx) = sender_balance.remove_transfer_inscription(inscription_id) else {
+      return Ok(None);
+    };
+
+    transfer_tx.complete(send_tx, sat_point);
+
+    if let Some(inscribed_transfer_tx) = self
+      .transfers
+      .iter_mut()
+      .find(|inscribed_transfer_tx| inscribed_transfer_tx.get_inscription_id() == inscription_id)
+    {
+      *inscribed_transfer_tx = transfer_tx.clone();
+    }
+
+    self.apply_transfer_send(&transfer_tx);
+
+    Ok(Some(transfer_tx))
+  }
+
+  /// Moves the amount of a sent transfer from its sender to its receiver,
+  /// after it has been removed from the sender's active transfers.
+  fn apply_transfer_send(&mut self, transfer_tx: &Brc20TransferTx) {
+    let Some(send_tx) = transfer_tx.get_send_tx() else {
+      return;
+    };
+
+    let decimals = self.decimals;
+
+    let sender_balance = self
+      .balances
+      .entry(transfer_tx.get_brc20_tx().get_owner().clone())
+      .or_insert_with(|| UserBalance::new(decimals));
+    sender_balance.decrease_overall_balance(transfer_tx.get_amount());
+    sender_balance.add_transfer_send(transfer_tx.clone());
+
+    let receiver_balance = self
+      .balances
+      .entry(send_tx.get_owner().clone())
+      .or_insert_with(|| UserBalance::new(decimals));
+    receiver_balance.increase_overall_balance(transfer_tx.get_amount());
+    receiver_balance.add_transfer_receive(transfer_tx.clone());
+
+    self.transfer_volume += transfer_tx.get_amount();
+  }
+
+  /// Recomputes balances, total minted, and transfer volume by replaying
+  /// stored mints, transfer inscriptions, and transfer sends in the order
+  /// they happened, by blocktime and then inscription number, discarding the
+  /// incrementally maintained state. A send sharing a blocktime with the
+  /// inscription of its transfer is replayed after it.
+  pub(crate) fn rebuild_balances(&mut self) {
+    enum Replay {
+      Mint(Brc20MintTx),
+      Inscribe(Brc20TransferTx),
+      Send(Brc20TransferTx),
+    }
+
+    impl Replay {
+      fn order(&self) -> (u64, i64, bool) {
+        match self {
+          Self::Mint(mint_tx) => (
+            mint_tx.get_brc20_tx().get_blocktime(),
+            mint_tx.get_inscription_number(),
+            false,
+          ),
+          Self::Inscribe(transfer_tx) => (
+            transfer_tx.get_brc20_tx().get_blocktime(),
+            transfer_tx.get_inscription_number(),
+            false,
+          ),
+          Self::Send(transfer_tx) => (
+            transfer_tx
+              .get_send_tx()
+              .map(Brc20Tx::get_blocktime)
+              .unwrap_or_default(),
+            transfer_tx.get_inscription_number(),
+            true,
+          ),
+        }
+      }
+    }
+
+    let mut replays = self
+      .mints
+      .iter()
+      .cloned()
+      .map(Replay::Mint)
+      .collect::<Vec<Replay>>();
+
+    for transfer_tx in &self.transfers {
+      replays.push(Replay::Inscribe(transfer_tx.clone()));
+
+      if transfer_tx.is_completed() {
+        replays.push(Replay::Send(transfer_tx.clone()));
+      }
+    }
+
+    replays.sort_by_key(Replay::order);
+
+    self.balances.clear();
+    self.total_minted = 0;
+    self.transfer_volume = 0;
+
+    for replay in replays {
+      match replay {
+        Replay::Mint(mint_tx) => {
+          self.total_minted += mint_tx.get_amount();
+          self
+            .balances
+            .entry(mint_tx.get_brc20_tx().get_owner().clone())
+            .or_insert_with(|| UserBalance::new(self.decimals))
+            .add_mint(mint_tx);
+        }
+        Replay::Inscribe(transfer_tx) => {
+          self
+            .balances
+            .entry(transfer_tx.get_brc20_tx().get_owner().clone())
+            .or_insert_with(|| UserBalance::new(self.decimals))
+            .add_transfer_inscription(transfer_tx);
+        }
+        Replay::Send(transfer_tx) => {
+          if let Some(sender_balance) = self
+            .balances
+            .get_mut(transfer_tx.get_brc20_tx().get_owner())
+          {
+            sender_balance.remove_transfer_inscription(transfer_tx.get_inscription_id());
+          }
+          self.apply_transfer_send(&transfer_tx);
+        }
+      }
+    }
+  }
+
+  pub(crate) fn stats(&self) -> Brc20TickerStats {
+    Brc20TickerStats {
+      holders: self
+        .balances
+        .values()
+        .filter(|user_balance| user_balance.get_overall_balance() > 0)
+        .count(),
+      total_minted: self.get_total_minted(),
+      circulating_supply: self.circulating_supply(),
+      transferred: self.get_transfer_volume(),
+      active_transfers: self
+        .balances
+        .values()
+        .map(|user_balance| user_balance.get_active_transfer_inscriptions().len())
+        .sum(),
+      mint_time_range: self.mint_time_range(),
+      holders_by_address_type: self.holders_by_address_type(),
+    }
+  }
+
+  /// Holders counted by the address type of their outputs. An owner's type
+  /// is taken from the mints, transfer inscriptions, and transfer sends that
+  /// paid to them.
+  pub(crate) fn holders_by_address_type(&self) -> BTreeMap<AddressType, usize> {
+    let address_types = self
+      .mints
+      .iter()
+      .map(Brc20MintTx::get_brc20_tx)
+      .chain(self.transfers.iter().flat_map(|transfer_tx| {
+        std::iter::once(transfer_tx.get_brc20_tx()).chain(transfer_tx.get_send_tx())
+      }))
+      .map(|brc20_tx| (brc20_tx.get_owner(), brc20_tx.get_address_type()))
+      .collect::<HashMap<&Owner, AddressType>>();
+
+    let mut holders_by_address_type = BTreeMap::new();
+
+    for (owner, user_balance) in &self.balances {
+      if user_balance.get_overall_balance() > 0 {
+        *holders_by_address_type
+          .entry(
+            address_types
+              .get(owner)
+              .copied()
+              .unwrap_or(AddressType::Unknown),
+          )
+          .or_default() += 1;
+      }
+    }
+
+    holders_by_address_type
+  }
+
+  /// Supply that can be spent freely: everything minted, less the amounts
+  /// locked in transfers that have been inscribed but not sent.
+  pub(crate) fn circulating_supply(&self) -> u128 {
+    self.total_minted.saturating_sub(
+      self
+        .balances
+        .values()
+        .map(UserBalance::get_transferable_balance)
+        .sum(),
+    )
+  }
+
+  /// The earliest and latest blocktimes of the ticker's mints.
+  pub(crate) fn mint_time_range(&self) -> Option<(u64, u64)> {
+    let blocktimes = self
+      .mints
+      .iter()
+      .map(|mint_tx| mint_tx.get_brc20_tx().get_blocktime());
+
+    Some((blocktimes.clone().min()?, blocktimes.max()?))
+  }
+
+  /// Amount minted per block, keyed by blocktime.
+  pub(crate) fn mints_per_block(&self) -> HashMap<u64, u128> {
+    let mut mints_per_block = HashMap::new();
+
+    for mint_tx in &self.mints {
+      *mints_per_block
+        .entry(mint_tx.get_brc20_tx().get_blocktime())
+        .or_default() += mint_tx.get_amount();
+    }
+
+    mints_per_block
+  }
+
+  pub(crate) fn summary(&self) -> TickerSummary {
+    TickerSummary {
+      ticker: self.display_tick.clone(),
+      decimals: self.decimals,
+      max_supply: self.get_max_supply(),
+      total_minted: self.get_total_minted(),
+      percent_minted: self.total_minted as f64 / self.max_supply as f64 * 100.0,
+      holders: self.stats().holders,
+      deploy_blocktime: self.deploy_tx.get_brc20_tx().get_blocktime(),
+    }
+  }
+
+  /// The lowercased ticker, for lookups.
+  pub(crate) fn get_ticker(&self) -> &str {
+    &self.tick
+  }
+
+  /// The ticker as deployed, for display.
+  pub(crate) fn get_display_ticker(&self) -> &str {
+    &self.display_tick
+  }
+
+  pub(crate) fn get_limit(&self) -> u128 {
+    self.limit
+  }
+
+  pub(crate) fn get_max_supply(&self) -> u128 {
+    self.max_supply
+  }
+
+  pub(crate) fn get_decimals(&self) -> u8 {
+    self.decimals
+  }
+
+  pub(crate) fn get_total_minted(&self) -> u128 {
+    self.total_minted
+  }
+
+  /// Total amount of completed transfers, as opposed to minted amounts.
+  pub(crate) fn get_transfer_volume(&self) -> u128 {
+    self.transfer_volume
+  }
+
+  pub(crate) fn is_fully_minted(&self) -> bool {
+    self.total_minted >= self.max_supply
+  }
+
+  pub(crate) fn get_deploy_tx(&self) -> &Brc20DeployTx {
+    &self.deploy_tx
+  }
+
+  pub(crate) fn get_mints(&self) -> &[Brc20MintTx] {
+    &self.mints
+  }
+
+  pub(crate) fn get_transfers(&self) -> &[Brc20TransferTx] {
+    &self.transfers
+  }
+
+  /// Inscribed transfers that haven't been sent yet, across all holders,
+  /// ordered by blocktime.
+  pub(crate) fn get_all_active_transfers(&self) -> Vec<&Brc20TransferTx> {
+    let mut transfers = self
+      .balances
+      .values()
+      .flat_map(|user_balance| user_balance.get_active_transfer_inscriptions().values())
+      .collect::<Vec<&Brc20TransferTx>>();
+
+    transfers.sort_by_key(|transfer_tx| {
+      let inscription_id = transfer_tx.get_inscription_id();
+      (
+        transfer_tx.get_brc20_tx().get_blocktime(),
+        inscription_id.txid,
+        inscription_id.index,
+      )
+    });
+
+    transfers
+  }
+
+  pub(crate) fn get_user_balance(&self, address: &Owner) -> Option<&UserBalance> {
+    self.balances.get(address)
+  }
+
+  pub(crate) fn get_user_balance_mut(&mut self, address: &Owner) -> Option<&mut UserBalance> {
+    self.balances.get_mut(address)
+  }
+
+  pub(crate) fn get_balances(&self) -> &HashMap<Owner, UserBalance> {
+    &self.balances
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ticker_with_sent_transfer() -> Brc20Ticker {
+    let mut tickers = HashMap::new();
+
+    let deploy_tx = Brc20DeployTx::new(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000"}"#).unwrap(),
+      DEFAULT_DECIMALS,
+    )
+    .validate_deploy_script(&tickers, &TickerLengthPolicy::default())
+    .unwrap();
+    tickers.insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+
+    let mint_tx = Brc20MintTx::new(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    )
+    .validate(&tickers)
+    .unwrap();
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    for (n, amt) in [(3, "30"), (4, "20")] {
+      let transfer_tx = Brc20TransferTx::new(
+        inscription_id(n),
+        n.into(),
+        brc20_tx(n.into(), &owner(2), 300),
+        transfer_script(&format!(
+          r#"{{"p":"brc-20","op":"transfer","tick":"ordi","amt":"{amt}"}}"#
+        ))
+        .unwrap(),
+      )
+      .handle_inscribe_transfer_amount(&mut tickers)
+      .unwrap();
+      tickers.get_mut("ordi").unwrap().add_transfer(transfer_tx);
+    }
+
+    let mut ticker = tickers.remove("ordi").unwrap();
+
+    ticker
+      .handle_transfer_send(
+        inscription_id(3),
+        &owner(2),
+        &owner(2),
+        brc20_tx(5, &owner(3), 400),
+        sat_point(5),
+      )
+      .unwrap();
+
+    ticker
+  }
+
+  #[test]
+  fn balance_at() {
+    let ticker = ticker_with_sent_transfer();
+
+    let sender = ticker.get_user_balance(&owner(2)).unwrap();
+    assert_eq!(sender.balance_at(199), 0);
+    assert_eq!(sender.balance_at(200), base_units("100", 18));
+    assert_eq!(sender.balance_at(399), base_units("100", 18));
+    assert_eq!(sender.balance_at(400), base_units("70", 18));
+    assert_eq!(sender.balance_at(u64::MAX), sender.get_overall_balance());
+
+    let receiver = ticker.get_user_balance(&owner(3)).unwrap();
+    assert_eq!(receiver.balance_at(399), 0);
+    assert_eq!(receiver.balance_at(400), base_units("30", 18));
+  }
+
+  #[test]
+  fn rebuild_balances_restores_drifted_state() {
+    let mut ticker = ticker_with_sent_transfer();
+
+    let expected = ticker.clone();
+
+    ticker
+      .get_user_balance_mut(&owner(2))
+      .unwrap()
+      .increase_overall_balance(5_000_000_000_000_000_000);
+    ticker
+      .get_user_balance_mut(&owner(3))
+      .unwrap()
+      .decrease_overall_balance(40_000_000_000_000_000_000);
+
+    assert_ne!(ticker, expected);
+
+    ticker.rebuild_balances();
+
+    assert_eq!(ticker, expected);
+    assert_eq!(ticker.get_total_minted(), base_units("100", 18));
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_available_balance(),
+      base_units("50", 18)
+    );
+  }
+
+  #[test]
+  fn rebuild_balances_replays_mints_and_sends_in_order() {
+    let mut ticker = ticker_with_sent_transfer();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(ticker.get_ticker().to_string(), ticker.clone());
+
+    ticker.add_mint(
+      Brc20MintTx::new(
+        inscription_id(6),
+        6,
+        brc20_tx(6, &owner(2), 500),
+        mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"10"}"#).unwrap(),
+      )
+      .validate(&tickers)
+      .unwrap(),
+    );
+
+    let expected = ticker.clone();
+
+    ticker.rebuild_balances();
+
+    assert_eq!(ticker, expected);
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_movements()
+        .iter()
+        .map(|movement| movement.new_balance)
+        .collect::<Vec<u128>>(),
+      [
+        base_units("100", 18),
+        base_units("70", 18),
+        base_units("80", 18)
+      ]
+    );
+  }
+
+  #[test]
+  fn stats_after_mint_and_completed_transfer() {
+    assert_eq!(
+      ticker_with_sent_transfer().stats(),
+      Brc20TickerStats {
+        holders: 2,
+        total_minted: base_units("100", 18),
+        circulating_supply: base_units("80", 18),
+        transferred: base_units("30", 18),
+        active_transfers: 1,
+        mint_time_range: Some((200, 200)),
+        holders_by_address_type: [(AddressType::Unknown, 2)].into(),
+      }
+    );
+  }
+
+  #[test]
+  fn mint_time_range() {
+    let mut ticker = ticker_with_sent_transfer();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(ticker.get_ticker().to_string(), ticker.clone());
+
+    for (n, blocktime) in [(6, 600), (7, 150)] {
+      ticker.add_mint(
+        Brc20MintTx::new(
+          inscription_id(n),
+          n.into(),
+          brc20_tx(n.into(), &owner(2), blocktime),
+          mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}"#).unwrap(),
+        )
+        .validate(&tickers)
+        .unwrap(),
+      );
+    }
+
+    assert_eq!(ticker.mint_time_range(), Some((150, 600)));
+    assert_eq!(ticker.stats().mint_time_range, Some((150, 600)));
+  }
+
+  #[test]
+  fn mints_per_block() {
+    let mut ticker = ticker_with_sent_transfer();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(ticker.get_ticker().to_string(), ticker.clone());
+
+    for (n, blocktime, amt) in [(6, 300, "5"), (7, 300, "2.5"), (8, 400, "1")] {
+      ticker.add_mint(
+        Brc20MintTx::new(
+          inscription_id(n),
+          n.into(),
+          brc20_tx(n.into(), &owner(2), blocktime),
+          mint_script(&format!(
+            r#"{{"p":"brc-20","op":"mint","tick":"ordi","amt":"{amt}"}}"#
+          ))
+          .unwrap(),
+        )
+        .validate(&tickers)
+        .unwrap(),
+      );
+    }
+
+    assert_eq!(
+      ticker.mints_per_block(),
+      [
+        (200, base_units("100", 18)),
+        (300, base_units("7.5", 18)),
+        (400, base_units("1", 18))
+      ]
+      .into()
+    );
+  }
+
+  #[test]
+  fn transfer_volume_counts_completed_transfers() {
+    let mut ticker = ticker_with_sent_transfer();
+    assert_eq!(ticker.get_transfer_volume(), base_units("30", 18));
+
+    ticker
+      .handle_transfer_send(
+        inscription_id(4),
+        &owner(2),
+        &owner(2),
+        brc20_tx(6, &owner(4), 500),
+        sat_point(6),
+      )
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(ticker.get_transfer_volume(), base_units("50", 18));
+    assert_eq!(ticker.stats().transferred, base_units("50", 18));
+    assert_eq!(ticker.get_total_minted(), base_units("100", 18));
+
+    ticker.rebuild_balances();
+    assert_eq!(ticker.get_transfer_volume(), base_units("50", 18));
+  }
+
+  #[test]
+  fn transfer_send_is_only_applied_once() {
+    let mut ticker = ticker_with_sent_transfer();
+
+    assert_eq!(
+      ticker.handle_transfer_send(
+        inscription_id(3),
+        &owner(2),
+        &owner(2),
+        brc20_tx(6, &owner(3), 500),
+        sat_point(6),
+      ),
+      Ok(None)
+    );
+
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("70", 18)
+    );
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(3))
+        .unwrap()
+        .get_overall_balance(),
+      base_units("30", 18)
+    );
+    assert_eq!(
+      ticker
+        .get_user_balance(&owner(3))
+        .unwrap()
+        .get_transfer_receives()
+        .len(),
+      1
+    );
+  }
+}
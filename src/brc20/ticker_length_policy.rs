@@ -0,0 +1,56 @@
+use {super::*, clap::ValueEnum};
+
+/// How the length of a ticker is measured. The spec counts UTF-8 bytes, but
+/// counting characters reproduces indexers that did, which matters for
+/// tickers with multibyte characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub(crate) enum TickerLengthMode {
+  #[default]
+  Bytes,
+  Chars,
+}
+
+impl TickerLengthMode {
+  fn len(self, tick: &str) -> usize {
+    match self {
+      Self::Bytes => tick.len(),
+      Self::Chars => tick.chars().count(),
+    }
+  }
+}
+
+/// Lengths a deployed ticker may have, measured according to `mode`.
+/// Self-mint deploys may use the self-mint lengths as well as the standard
+/// ones. By default tickers are 4 bytes, and 5 byte tickers are reserved for
+/// self-mint deploys, which only the deployer can mint.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TickerLengthPolicy {
+  standard: BTreeSet<usize>,
+  self_mint: BTreeSet<usize>,
+  mode: TickerLengthMode,
+}
+
+impl TickerLengthPolicy {
+  pub(crate) fn new(
+    standard: impl IntoIterator<Item = usize>,
+    self_mint: impl IntoIterator<Item = usize>,
+    mode: TickerLengthMode,
+  ) -> Self {
+    Self {
+      standard: standard.into_iter().collect(),
+      self_mint: self_mint.into_iter().collect(),
+      mode,
+    }
+  }
+
+  pub(crate) fn allows(&self, tick: &str, self_mint: bool) -> bool {
+    let len = self.mode.len(tick);
+    self.standard.contains(&len) || (self_mint && self.self_mint.contains(&len))
+  }
+}
+
+impl Default for TickerLengthPolicy {
+  fn default() -> Self {
+    Self::new([4], [5], TickerLengthMode::default())
+  }
+}
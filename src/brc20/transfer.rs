@@ -0,0 +1,503 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Brc20Transfer {
+  pub(crate) p: String,
+  pub(crate) op: String,
+  pub(crate) tick: String,
+  pub(crate) amt: String,
+}
+
+/// Whether a transfer inscription has been sent yet. Serialized inline in
+/// its `Brc20TransferTx`, as `"state": "inscribed"`, or as
+/// `"state": "completed"` alongside the `send_tx` that sent it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub(crate) enum TransferState {
+  Inscribed,
+  Completed { send_tx: Brc20Tx },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Brc20TransferTx {
+  inscription_id: InscriptionId,
+  inscription_number: i64,
+  brc20_tx: Brc20Tx,
+  transfer_script: Brc20Transfer,
+  /// In base units.
+  #[serde(serialize_with = "serialize_base_units")]
+  amount: u128,
+  decimals: u8,
+  sat_point: SatPoint,
+  #[serde(flatten)]
+  state: TransferState,
+}
+
+impl Brc20TransferTx {
+  pub(crate) fn new(
+    inscription_id: InscriptionId,
+    inscription_number: i64,
+    brc20_tx: Brc20Tx,
+    transfer_script: Brc20Transfer,
+  ) -> Self {
+    Self {
+      inscription_id,
+      inscription_number,
+      sat_point: SatPoint {
+        outpoint: OutPoint {
+          txid: brc20_tx.get_txid(),
+          vout: brc20_tx.get_vout(),
+        },
+        offset: 0,
+      },
+      brc20_tx,
+      transfer_script,
+      amount: 0,
+      decimals: 18,
+      state: TransferState::Inscribed,
+    }
+  }
+
+  pub(crate) fn handle_inscribe_transfer_amount(
+    mut self,
+    tickers: &mut HashMap<String, Brc20Ticker>,
+  ) -> Result<Self, Brc20Error> {
+    let ticker = tickers
+      .get_mut(&self.get_ticker())
+      .ok_or_else(|| Brc20Error::TickerNotFound(self.transfer_script.tick.clone()))?;
+
+    self.decimals = ticker.get_decimals();
+    self.amount = convert_to_base_units(&self.transfer_script.amt, self.decimals)?;
+
+    if self.amount == 0 {
+      return Err(Brc20Error::InvalidAmount);
+    }
+
+    let user_balance = ticker
+      .get_user_balance_mut(self.brc20_tx.get_owner())
+      .ok_or(Brc20Error::UserBalanceNotFound)?;
+
+    let available_balance = user_balance.get_available_balance();
+
+    if self.amount > available_balance {
+      return Err(Brc20Error::InsufficientBalance {
+        tick: self.transfer_script.tick,
+        amount: format_base_units(self.amount, self.decimals),
+        available: format_base_units(available_balance, self.decimals),
+      });
+    }
+
+    user_balance.add_transfer_inscription(self.clone());
+
+    Ok(self)
+  }
+
+  pub(crate) fn get_inscription_id(&self) -> InscriptionId {
+    self.inscription_id
+  }
+
+  pub(crate) fn get_inscription_number(&self) -> i64 {
+    self.inscription_number
+  }
+
+  pub(crate) fn get_brc20_tx(&self) -> &Brc20Tx {
+    &self.brc20_tx
+  }
+
+  pub(crate) fn get_ticker(&self) -> String {
+    self.transfer_script.tick.to_lowercase()
+  }
+
+  pub(crate) fn get_transfer_script(&self) -> &Brc20Transfer {
+    &self.transfer_script
+  }
+
+  pub(crate) fn is_completed(&self) -> bool {
+    matches!(self.state, TransferState::Completed { .. })
+  }
+
+  /// The transaction that sent the transfer, only once it's completed.
+  pub(crate) fn get_send_tx(&self) -> Option<&Brc20Tx> {
+    match &self.state {
+      TransferState::Inscribed => None,
+      TransferState::Completed { send_tx } => Some(send_tx),
+    }
+  }
+
+  /// The owner of the output the transfer was sent to, only once it's
+  /// completed.
+  pub(crate) fn get_receiver(&self) -> Option<&Owner> {
+    self.get_send_tx().map(Brc20Tx::get_owner)
+  }
+
+  /// The location of the sat carrying the transfer inscription, either in
+  /// its reveal transaction or, once sent, in the send transaction.
+  pub(crate) fn get_sat_point(&self) -> SatPoint {
+    self.sat_point
+  }
+
+  pub(crate) fn complete(&mut self, send_tx: Brc20Tx, sat_point: SatPoint) {
+    self.state = TransferState::Completed { send_tx };
+    self.sat_point = sat_point;
+  }
+
+  pub(crate) fn get_amount(&self) -> u128 {
+    self.amount
+  }
+}
+
+impl Display for Brc20TransferTx {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(
+      f,
+      "Transfer of {} {} inscribed by {} in {}",
+      format_base_units(self.amount, self.decimals),
+      self.transfer_script.tick,
+      self.brc20_tx.get_owner(),
+      self.brc20_tx.get_txid(),
+    )?;
+
+    if let TransferState::Completed { send_tx } = &self.state {
+      write!(
+        f,
+        ", sent to {} in {}",
+        send_tx.get_owner(),
+        send_tx.get_txid()
+      )?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_transfer() {
+    assert_eq!(
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"100"}"#),
+      Some(Brc20Transfer {
+        p: "brc-20".into(),
+        op: "transfer".into(),
+        tick: "ordi".into(),
+        amt: "100".into(),
+      })
+    );
+  }
+
+  #[test]
+  fn transfer_with_unknown_field_is_rejected() {
+    assert_eq!(
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"100","to":"bc1q"}"#),
+      None
+    );
+  }
+
+  #[test]
+  fn mint_is_not_parsed_as_transfer() {
+    assert_eq!(
+      transfer_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#),
+      None
+    );
+  }
+
+  fn tickers() -> HashMap<String, Brc20Ticker> {
+    let deploy_tx = Brc20DeployTx::new(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000"}"#).unwrap(),
+      DEFAULT_DECIMALS,
+    )
+    .validate_deploy_script(&HashMap::new(), &TickerLengthPolicy::default())
+    .unwrap();
+
+    let mut tickers = HashMap::new();
+    tickers.insert(deploy_tx.get_ticker(), Brc20Ticker::new(deploy_tx));
+
+    let mint_tx = Brc20MintTx::new(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"100"}"#).unwrap(),
+    )
+    .validate(&tickers)
+    .unwrap();
+    tickers.get_mut("ordi").unwrap().add_mint(mint_tx);
+
+    tickers
+  }
+
+  fn transfer(n: u32, owner: &Owner, amt: &str) -> Brc20TransferTx {
+    Brc20TransferTx::new(
+      inscription_id(n),
+      n.into(),
+      brc20_tx(n.into(), owner, 300),
+      transfer_script(&format!(
+        r#"{{"p":"brc-20","op":"transfer","tick":"ordi","amt":"{amt}"}}"#
+      ))
+      .unwrap(),
+    )
+  }
+
+  #[test]
+  fn valid_transfer_inscription() {
+    let mut tickers = tickers();
+
+    let transfer_tx = transfer(3, &owner(2), "30")
+      .handle_inscribe_transfer_amount(&mut tickers)
+      .unwrap();
+    assert_eq!(transfer_tx.get_amount(), base_units("30", 18));
+
+    let user_balance = tickers["ordi"].get_user_balance(&owner(2)).unwrap();
+    assert_eq!(user_balance.get_overall_balance(), base_units("100", 18));
+    assert_eq!(
+      user_balance.get_transferable_balance(),
+      base_units("30", 18)
+    );
+    assert_eq!(user_balance.get_available_balance(), base_units("70", 18));
+  }
+
+  #[test]
+  fn transfer_of_remaining_balance_is_exact() {
+    let mut tickers = tickers();
+
+    transfer(3, &owner(2), "99.7")
+      .handle_inscribe_transfer_amount(&mut tickers)
+      .unwrap();
+
+    assert_eq!(
+      tickers["ordi"]
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_available_balance(),
+      300_000_000_000_000_000
+    );
+
+    assert_eq!(
+      transfer(4, &owner(2), "0.3")
+        .handle_inscribe_transfer_amount(&mut tickers)
+        .unwrap()
+        .get_amount(),
+      base_units("0.3", 18)
+    );
+
+    assert_eq!(
+      tickers["ordi"]
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_available_balance(),
+      0
+    );
+  }
+
+  #[test]
+  fn transfer_just_over_available_balance_is_invalid() {
+    let mut tickers = tickers();
+
+    assert_eq!(
+      transfer(3, &owner(2), "100.000000000000000001")
+        .handle_inscribe_transfer_amount(&mut tickers),
+      Err(Brc20Error::InsufficientBalance {
+        tick: "ordi".into(),
+        amount: "100.000000000000000001".into(),
+        available: "100.0".into(),
+      })
+    );
+  }
+
+  #[test]
+  fn available_balance_does_not_go_below_zero() {
+    let mut tickers = tickers();
+
+    transfer(3, &owner(2), "30")
+      .handle_inscribe_transfer_amount(&mut tickers)
+      .unwrap();
+
+    let user_balance = tickers
+      .get_mut("ordi")
+      .unwrap()
+      .get_user_balance_mut(&owner(2))
+      .unwrap();
+    user_balance.decrease_overall_balance(90_000_000_000_000_000_000);
+
+    assert_eq!(user_balance.get_overall_balance(), base_units("10", 18));
+    assert_eq!(
+      user_balance.get_transferable_balance(),
+      base_units("30", 18)
+    );
+    assert_eq!(user_balance.get_available_balance(), 0);
+
+    assert_eq!(
+      transfer(4, &owner(2), "1").handle_inscribe_transfer_amount(&mut tickers),
+      Err(Brc20Error::InsufficientBalance {
+        tick: "ordi".into(),
+        amount: "1.0".into(),
+        available: "0.0".into(),
+      })
+    );
+  }
+
+  #[test]
+  fn uppercase_transfer_ticker_matches_lowercase_deploy() {
+    let mut tickers = tickers();
+
+    let transfer_tx = Brc20TransferTx::new(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ORDI","amt":"30"}"#).unwrap(),
+    )
+    .handle_inscribe_transfer_amount(&mut tickers)
+    .unwrap();
+
+    assert_eq!(transfer_tx.get_ticker(), "ordi");
+    assert_eq!(
+      tickers["ordi"]
+        .get_user_balance(&owner(2))
+        .unwrap()
+        .get_transferable_balance(),
+      base_units("30", 18)
+    );
+  }
+
+  #[test]
+  fn transfer_with_empty_amount_is_invalid() {
+    assert_eq!(
+      transfer(3, &owner(2), "").handle_inscribe_transfer_amount(&mut tickers()),
+      Err(Brc20Error::MissingNumber)
+    );
+  }
+
+  #[test]
+  fn transfer_without_balance_is_invalid() {
+    assert_eq!(
+      transfer(3, &owner(3), "30").handle_inscribe_transfer_amount(&mut tickers()),
+      Err(Brc20Error::UserBalanceNotFound)
+    );
+  }
+
+  #[test]
+  fn transfer_above_available_balance_is_invalid() {
+    let mut tickers = tickers();
+
+    transfer(3, &owner(2), "30")
+      .handle_inscribe_transfer_amount(&mut tickers)
+      .unwrap();
+
+    let err = transfer(4, &owner(2), "80.5")
+      .handle_inscribe_transfer_amount(&mut tickers)
+      .unwrap_err();
+
+    assert_eq!(
+      err,
+      Brc20Error::InsufficientBalance {
+        tick: "ordi".into(),
+        amount: "80.5".into(),
+        available: "70.0".into(),
+      }
+    );
+
+    let reason = err.to_string();
+    assert!(reason.contains("80.5"), "{reason}");
+    assert!(reason.contains("70.0"), "{reason}");
+    assert!(reason.contains("ordi"), "{reason}");
+  }
+
+  #[test]
+  fn transfer_of_unknown_ticker_is_invalid() {
+    assert_eq!(
+      transfer(3, &owner(2), "1").handle_inscribe_transfer_amount(&mut HashMap::new()),
+      Err(Brc20Error::TickerNotFound("ordi".into()))
+    );
+  }
+
+  #[test]
+  fn display_formats_amount_with_decimals() {
+    let transfer_tx = Brc20TransferTx {
+      inscription_id: inscription_id(1),
+      inscription_number: 1,
+      brc20_tx: brc20_tx(1, &owner(1), 100),
+      transfer_script: transfer_script(
+        r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"0.5"}"#,
+      )
+      .unwrap(),
+      amount: 50_000_000,
+      decimals: 8,
+      sat_point: SatPoint {
+        outpoint: OutPoint {
+          txid: txid(1),
+          vout: 0,
+        },
+        offset: 0,
+      },
+      state: TransferState::Inscribed,
+    };
+
+    assert_eq!(
+      transfer_tx.to_string(),
+      format!(
+        "Transfer of 0.5 ordi inscribed by {} in {}",
+        owner(1),
+        txid(1)
+      )
+    );
+  }
+
+  #[test]
+  fn inscribed_transfer_has_no_send_tx_or_receiver() {
+    let transfer_tx = transfer(3, &owner(2), "30");
+
+    assert_eq!(transfer_tx.state, TransferState::Inscribed);
+    assert!(!transfer_tx.is_completed());
+    assert_eq!(transfer_tx.get_send_tx(), None);
+    assert_eq!(transfer_tx.get_receiver(), None);
+
+    let json = serde_json::to_value(&transfer_tx).unwrap();
+    assert_eq!(json["state"], "inscribed");
+    assert!(json.get("send_tx").is_none());
+  }
+
+  #[test]
+  fn completed_transfer_exposes_send_tx_and_receiver() {
+    let mut transfer_tx = transfer(3, &owner(2), "30");
+    transfer_tx.complete(brc20_tx(4, &owner(3), 400), sat_point(4));
+
+    assert!(transfer_tx.is_completed());
+    assert_eq!(
+      transfer_tx.state,
+      TransferState::Completed {
+        send_tx: brc20_tx(4, &owner(3), 400)
+      }
+    );
+    assert_eq!(
+      transfer_tx.get_send_tx(),
+      Some(&brc20_tx(4, &owner(3), 400))
+    );
+    assert_eq!(transfer_tx.get_receiver(), Some(&owner(3)));
+    assert_eq!(transfer_tx.get_sat_point(), sat_point(4));
+
+    let json = serde_json::to_value(&transfer_tx).unwrap();
+    assert_eq!(json["state"], "completed");
+    assert_eq!(json["send_tx"]["txid"], txid(4).to_string());
+
+    assert_eq!(
+      transfer_tx.to_string(),
+      format!(
+        "Transfer of 0.0 ordi inscribed by {} in {}, sent to {} in {}",
+        owner(2),
+        txid(3),
+        owner(3),
+        txid(4)
+      )
+    );
+  }
+
+  #[test]
+  fn invalid_json_is_rejected() {
+    assert_eq!(transfer_script("transfer"), None);
+  }
+}
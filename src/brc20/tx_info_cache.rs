@@ -0,0 +1,178 @@
+use super::*;
+
+/// Raw transaction info shared by all fetch threads, so that inscriptions
+/// revealed in, or sent through, the same transaction only cost one RPC
+/// call. Holds at most `capacity` transactions, evicting the least recently
+/// used one when full. A capacity of zero disables caching.
+#[derive(Clone)]
+pub(crate) struct TxInfoCache {
+  capacity: usize,
+  inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+  entries: HashMap<Txid, (u64, GetRawTransactionResult)>,
+  recency: BTreeMap<u64, Txid>,
+  next_use: u64,
+}
+
+impl Inner {
+  fn touch(&mut self, txid: Txid) -> Option<GetRawTransactionResult> {
+    let next_use = self.next_use;
+    let (last_use, raw_tx_result) = self.entries.get_mut(&txid)?;
+    self.recency.remove(&*last_use);
+    self.recency.insert(next_use, txid);
+    *last_use = next_use;
+    self.next_use += 1;
+    Some(raw_tx_result.clone())
+  }
+
+  fn insert(&mut self, capacity: usize, raw_tx_result: GetRawTransactionResult) {
+    let txid = raw_tx_result.txid;
+
+    if let Some((last_use, _)) = self.entries.remove(&txid) {
+      self.recency.remove(&last_use);
+    }
+
+    while self.entries.len() >= capacity {
+      let Some((_, evicted)) = self.recency.pop_first() else {
+        break;
+      };
+      self.entries.remove(&evicted);
+    }
+
+    self.recency.insert(self.next_use, txid);
+    self.entries.insert(txid, (self.next_use, raw_tx_result));
+    self.next_use += 1;
+  }
+}
+
+impl TxInfoCache {
+  pub(crate) const DEFAULT_CAPACITY: usize = 10_000;
+
+  pub(crate) fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      inner: Arc::new(Mutex::new(Inner::default())),
+    }
+  }
+
+  pub(crate) fn get_raw_transaction_info(
+    &self,
+    tx_provider: &dyn TxProvider,
+    txid: Txid,
+  ) -> Result<Option<GetRawTransactionResult>> {
+    self.get_or_fetch(txid, || tx_provider.get_raw_transaction_info(txid))
+  }
+
+  /// The lock isn't held while fetching, so threads missing on different
+  /// transactions don't wait on each other.
+  fn get_or_fetch(
+    &self,
+    txid: Txid,
+    fetch: impl FnOnce() -> Result<Option<GetRawTransactionResult>>,
+  ) -> Result<Option<GetRawTransactionResult>> {
+    if self.capacity == 0 {
+      return fetch();
+    }
+
+    if let Some(raw_tx_result) = self.inner.lock().unwrap().touch(txid) {
+      return Ok(Some(raw_tx_result));
+    }
+
+    let raw_tx_result = fetch()?;
+
+    if let Some(raw_tx_result) = &raw_tx_result {
+      self
+        .inner
+        .lock()
+        .unwrap()
+        .insert(self.capacity, raw_tx_result.clone());
+    }
+
+    Ok(raw_tx_result)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, std::cell::Cell};
+
+  fn fetch<'a>(
+    calls: &'a Cell<usize>,
+    n: u64,
+  ) -> impl FnOnce() -> Result<Option<GetRawTransactionResult>> + 'a {
+    move || {
+      calls.set(calls.get() + 1);
+      Ok(Some(raw_tx_result(n, &owner(1), 100)))
+    }
+  }
+
+  #[test]
+  fn inscriptions_sharing_a_reveal_transaction_fetch_it_once() {
+    let cache = TxInfoCache::new(10);
+    let calls = Cell::new(0);
+
+    let first = cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+    let second = cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+
+    assert_eq!(calls.get(), 1);
+    assert_eq!(first, second);
+    assert_eq!(
+      get_owner_of_output(
+        &OutPoint {
+          txid: txid(1),
+          vout: 0
+        },
+        &second.unwrap(),
+        Network::Bitcoin
+      ),
+      Ok(owner(1))
+    );
+  }
+
+  #[test]
+  fn least_recently_used_transaction_is_evicted() {
+    let cache = TxInfoCache::new(2);
+    let calls = Cell::new(0);
+
+    cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+    cache.get_or_fetch(txid(2), fetch(&calls, 2)).unwrap();
+    cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+    cache.get_or_fetch(txid(3), fetch(&calls, 3)).unwrap();
+    assert_eq!(calls.get(), 3);
+
+    cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+    assert_eq!(calls.get(), 3);
+
+    cache.get_or_fetch(txid(2), fetch(&calls, 2)).unwrap();
+    assert_eq!(calls.get(), 4);
+  }
+
+  #[test]
+  fn zero_capacity_disables_caching() {
+    let cache = TxInfoCache::new(0);
+    let calls = Cell::new(0);
+
+    cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+    cache.get_or_fetch(txid(1), fetch(&calls, 1)).unwrap();
+
+    assert_eq!(calls.get(), 2);
+  }
+
+  #[test]
+  fn missing_transactions_are_not_cached() {
+    let cache = TxInfoCache::new(10);
+    let calls = Cell::new(0);
+
+    let missing = || {
+      calls.set(calls.get() + 1);
+      Ok(None)
+    };
+
+    assert_eq!(cache.get_or_fetch(txid(1), missing).unwrap(), None);
+    assert_eq!(cache.get_or_fetch(txid(1), missing).unwrap(), None);
+    assert_eq!(calls.get(), 2);
+  }
+}
@@ -0,0 +1,95 @@
+use super::*;
+
+/// Source of raw transaction info, used for everything the BRC-20 indexer
+/// needs to know about a transaction beyond what the ord index stores.
+pub(crate) trait TxProvider: Sync {
+  fn get_raw_transaction_info(&self, txid: Txid) -> Result<Option<GetRawTransactionResult>>;
+}
+
+/// Fetches transactions from bitcoind over RPC.
+impl TxProvider for Index {
+  fn get_raw_transaction_info(&self, txid: Txid) -> Result<Option<GetRawTransactionResult>> {
+    Index::get_raw_transaction_info(self, txid)
+  }
+}
+
+/// Serves transactions from an export of verbose `getrawtransaction`
+/// results, so indexing doesn't need bitcoind to answer transaction lookups.
+/// Transactions missing from the export are treated as not found.
+#[derive(Debug, Default)]
+pub(crate) struct LocalTxProvider {
+  transactions: HashMap<Txid, GetRawTransactionResult>,
+}
+
+impl LocalTxProvider {
+  pub(crate) fn new(transactions: impl IntoIterator<Item = GetRawTransactionResult>) -> Self {
+    Self {
+      transactions: transactions
+        .into_iter()
+        .map(|raw_tx_result| (raw_tx_result.txid, raw_tx_result))
+        .collect(),
+    }
+  }
+
+  /// Loads a JSON array of verbose `getrawtransaction` results from `path`.
+  pub(crate) fn load(path: &Path) -> Result<Self> {
+    let transactions: Vec<GetRawTransactionResult> = serde_json::from_reader(
+      File::open(path).with_context(|| format!("failed to open {}", path.display()))?,
+    )
+    .with_context(|| format!("failed to parse raw transactions from {}", path.display()))?;
+
+    Ok(Self::new(transactions))
+  }
+}
+
+impl TxProvider for LocalTxProvider {
+  fn get_raw_transaction_info(&self, txid: Txid) -> Result<Option<GetRawTransactionResult>> {
+    Ok(self.transactions.get(&txid).cloned())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn local_transactions_are_found_by_txid() {
+    let tx_provider = LocalTxProvider::new([
+      raw_tx_result(1, &owner(1), 100),
+      raw_tx_result(2, &owner(2), 200),
+    ]);
+
+    assert_eq!(
+      tx_provider.get_raw_transaction_info(txid(2)).unwrap(),
+      Some(raw_tx_result(2, &owner(2), 200))
+    );
+    assert_eq!(tx_provider.get_raw_transaction_info(txid(3)).unwrap(), None);
+  }
+
+  #[test]
+  fn local_transactions_are_loaded_from_file() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("raw-txs.json");
+
+    fs::write(
+      &path,
+      serde_json::to_string(&[
+        raw_tx_result(1, &owner(1), 100),
+        raw_tx_result(2, &owner(2), 200),
+      ])
+      .unwrap(),
+    )
+    .unwrap();
+
+    let tx_provider = LocalTxProvider::load(&path).unwrap();
+
+    assert_eq!(
+      tx_provider.get_raw_transaction_info(txid(1)).unwrap(),
+      Some(raw_tx_result(1, &owner(1), 100))
+    );
+    assert_eq!(
+      tx_provider.get_raw_transaction_info(txid(2)).unwrap(),
+      Some(raw_tx_result(2, &owner(2), 200))
+    );
+  }
+}
@@ -0,0 +1,184 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MovementCause {
+  Mint,
+  Send,
+  Receive,
+}
+
+/// A change to the overall balance of a holder, in the order the changes
+/// were applied. The amount is in base units and is deducted for sends and
+/// added otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct BalanceMovement {
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) amount: u128,
+  #[serde(serialize_with = "serialize_base_units")]
+  pub(crate) new_balance: u128,
+  pub(crate) cause: MovementCause,
+  pub(crate) txid: Txid,
+  pub(crate) blocktime: u64,
+}
+
+/// Balances are kept in base units.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct UserBalance {
+  #[serde(serialize_with = "serialize_base_units")]
+  overall_balance: u128,
+  decimals: u8,
+  mints: Vec<Brc20MintTx>,
+  active_transfer_inscriptions: HashMap<InscriptionId, Brc20TransferTx>,
+  transfer_sends: Vec<Brc20TransferTx>,
+  transfer_receives: Vec<Brc20TransferTx>,
+  #[serde(skip)]
+  movements: Vec<BalanceMovement>,
+}
+
+impl UserBalance {
+  pub(crate) fn new(decimals: u8) -> Self {
+    Self {
+      decimals,
+      ..Self::default()
+    }
+  }
+
+  pub(crate) fn get_overall_balance(&self) -> u128 {
+    self.overall_balance
+  }
+
+  pub(crate) fn get_transferable_balance(&self) -> u128 {
+    self
+      .active_transfer_inscriptions
+      .values()
+      .map(|transfer_tx| transfer_tx.get_amount())
+      .sum()
+  }
+
+  pub(crate) fn get_available_balance(&self) -> u128 {
+    let transferable_balance = self.get_transferable_balance();
+
+    if transferable_balance > self.overall_balance {
+      log::warn!(
+        "transferable balance {} exceeds overall balance {}",
+        format_base_units(transferable_balance, self.decimals),
+        format_base_units(self.overall_balance, self.decimals),
+      );
+      return 0;
+    }
+
+    self.overall_balance - transferable_balance
+  }
+
+  /// Balance as of the block at `height`, counting only mints, sends, and
+  /// receives mined at or below it.
+  pub(crate) fn balance_at(&self, height: u64) -> u128 {
+    let minted = self
+      .mints
+      .iter()
+      .filter(|mint_tx| mint_tx.get_brc20_tx().get_height() <= height)
+      .map(|mint_tx| mint_tx.get_amount())
+      .sum::<u128>();
+
+    let sent_before = |transfer_tx: &&Brc20TransferTx| {
+      transfer_tx
+        .get_send_tx()
+        .map(|send_tx| send_tx.get_height() <= height)
+        .unwrap_or_default()
+    };
+
+    let received = self
+      .transfer_receives
+      .iter()
+      .filter(sent_before)
+      .map(|transfer_tx| transfer_tx.get_amount())
+      .sum::<u128>();
+
+    let sent = self
+      .transfer_sends
+      .iter()
+      .filter(sent_before)
+      .map(|transfer_tx| transfer_tx.get_amount())
+      .sum::<u128>();
+
+    (minted + received).saturating_sub(sent)
+  }
+
+  pub(crate) fn add_mint(&mut self, mint_tx: Brc20MintTx) {
+    self.overall_balance += mint_tx.get_amount();
+    self.record_movement(
+      mint_tx.get_amount(),
+      MovementCause::Mint,
+      mint_tx.get_brc20_tx(),
+    );
+    self.mints.push(mint_tx);
+  }
+
+  pub(crate) fn increase_overall_balance(&mut self, base_units: u128) {
+    self.overall_balance += base_units;
+  }
+
+  pub(crate) fn decrease_overall_balance(&mut self, base_units: u128) {
+    self.overall_balance = self.overall_balance.saturating_sub(base_units);
+  }
+
+  pub(crate) fn add_transfer_inscription(&mut self, transfer_tx: Brc20TransferTx) {
+    self
+      .active_transfer_inscriptions
+      .insert(transfer_tx.get_inscription_id(), transfer_tx);
+  }
+
+  pub(crate) fn remove_transfer_inscription(
+    &mut self,
+    inscription_id: InscriptionId,
+  ) -> Option<Brc20TransferTx> {
+    self.active_transfer_inscriptions.remove(&inscription_id)
+  }
+
+  /// Records a sent transfer, after its amount has been deducted from the
+  /// overall balance.
+  pub(crate) fn add_transfer_send(&mut self, transfer_tx: Brc20TransferTx) {
+    if let Some(send_tx) = transfer_tx.get_send_tx() {
+      self.record_movement(transfer_tx.get_amount(), MovementCause::Send, send_tx);
+    }
+    self.transfer_sends.push(transfer_tx);
+  }
+
+  /// Records a received transfer, after its amount has been added to the
+  /// overall balance.
+  pub(crate) fn add_transfer_receive(&mut self, transfer_tx: Brc20TransferTx) {
+    if let Some(send_tx) = transfer_tx.get_send_tx() {
+      self.record_movement(transfer_tx.get_amount(), MovementCause::Receive, send_tx);
+    }
+    self.transfer_receives.push(transfer_tx);
+  }
+
+  fn record_movement(&mut self, amount: u128, cause: MovementCause, brc20_tx: &Brc20Tx) {
+    self.movements.push(BalanceMovement {
+      amount,
+      new_balance: self.overall_balance,
+      cause,
+      txid: brc20_tx.get_txid(),
+      blocktime: brc20_tx.get_blocktime(),
+    });
+  }
+
+  pub(crate) fn get_movements(&self) -> &[BalanceMovement] {
+    &self.movements
+  }
+
+  pub(crate) fn get_transfer_sends(&self) -> &[Brc20TransferTx] {
+    &self.transfer_sends
+  }
+
+  pub(crate) fn get_transfer_receives(&self) -> &[Brc20TransferTx] {
+    &self.transfer_receives
+  }
+
+  pub(crate) fn get_active_transfer_inscriptions(
+    &self,
+  ) -> &HashMap<InscriptionId, Brc20TransferTx> {
+    &self.active_transfer_inscriptions
+  }
+}
@@ -0,0 +1,262 @@
+use super::*;
+
+/// Parses a plain decimal amount into an exact count of base units, the
+/// smallest amount `decimals` allows. Signs, exponents, and anything else
+/// beyond digits and one decimal point are rejected.
+pub(crate) fn convert_to_base_units(number_string: &str, decimals: u8) -> Result<u128, Brc20Error> {
+  check_number(number_string, decimals)?;
+
+  let (whole, fraction) = number_string.split_once('.').unwrap_or((number_string, ""));
+
+  if whole.is_empty() && fraction.is_empty() {
+    return Err(Brc20Error::InvalidNumber(number_string.into()));
+  }
+
+  let digits = format!("{whole}{fraction:0<width$}", width = usize::from(decimals));
+
+  digits
+    .trim_start_matches('0')
+    .chars()
+    .try_fold(0u128, |base_units, digit| {
+      base_units
+        .checked_mul(10)?
+        .checked_add(digit.to_digit(10)?.into())
+    })
+    .ok_or_else(|| Brc20Error::NumberTooLarge(number_string.into()))
+}
+
+/// Formats `base_units` exactly, with at least one fractional digit unless
+/// `decimals` is zero and without trailing zeros.
+pub(crate) fn format_base_units(base_units: u128, decimals: u8) -> String {
+  let scale = 10u128.pow(decimals.into());
+  let whole = base_units / scale;
+
+  if decimals == 0 {
+    return whole.to_string();
+  }
+
+  let fraction = format!(
+    "{:0width$}",
+    base_units % scale,
+    width = usize::from(decimals)
+  );
+
+  match fraction.trim_end_matches('0') {
+    "" => format!("{whole}.0"),
+    fraction => format!("{whole}.{fraction}"),
+  }
+}
+
+/// Base units can be too large for JSON numbers, so they're serialized as
+/// strings.
+pub(crate) fn serialize_base_units<S>(base_units: &u128, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: Serializer,
+{
+  serializer.collect_str(base_units)
+}
+
+fn check_number(number_string: &str, decimals: u8) -> Result<(), Brc20Error> {
+  let malformed = |reason| Brc20Error::MalformedNumber {
+    number: number_string.into(),
+    reason,
+  };
+
+  if number_string.is_empty() {
+    return Err(Brc20Error::MissingNumber);
+  }
+
+  if number_string.starts_with('-') {
+    return Err(malformed("negative amounts are not allowed"));
+  }
+
+  if number_string.starts_with('+') {
+    return Err(malformed("leading plus sign is not allowed"));
+  }
+
+  if number_string.contains(['e', 'E']) {
+    return Err(malformed("scientific notation is not allowed"));
+  }
+
+  if number_string.matches('.').count() > 1 {
+    return Err(malformed("more than one decimal point"));
+  }
+
+  if !number_string
+    .chars()
+    .all(|c| c.is_ascii_digit() || c == '.')
+  {
+    return Err(Brc20Error::InvalidNumber(number_string.into()));
+  }
+
+  if let Some((_, fraction)) = number_string.split_once('.') {
+    if fraction.len() > usize::from(decimals) {
+      return Err(Brc20Error::TooManyDecimals { decimals });
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn convert_to_base_units_rejects_too_many_decimals() {
+    assert_eq!(
+      convert_to_base_units("0.000000001", 8),
+      Err(Brc20Error::TooManyDecimals { decimals: 8 })
+    );
+    assert_eq!(
+      convert_to_base_units("1.5", 0),
+      Err(Brc20Error::TooManyDecimals { decimals: 0 })
+    );
+  }
+
+  #[test]
+  fn convert_to_base_units_rejects_empty_string() {
+    let err = convert_to_base_units("", 18).unwrap_err();
+    assert_eq!(err, Brc20Error::MissingNumber);
+    assert_eq!(err.to_string(), "Missing required numeric field");
+  }
+
+  #[test]
+  fn convert_to_base_units_rejects_non_numbers() {
+    assert_eq!(
+      convert_to_base_units("abc", 18),
+      Err(Brc20Error::InvalidNumber("abc".into()))
+    );
+  }
+
+  #[test]
+  fn convert_to_base_units_rejects_malformed_numbers() {
+    for (number, reason) in [
+      ("-1", "negative amounts are not allowed"),
+      ("1e3", "scientific notation is not allowed"),
+      ("1E3", "scientific notation is not allowed"),
+      ("1.2.3", "more than one decimal point"),
+      ("+5", "leading plus sign is not allowed"),
+    ] {
+      assert_eq!(
+        convert_to_base_units(number, 18),
+        Err(Brc20Error::MalformedNumber {
+          number: number.into(),
+          reason,
+        }),
+        "{number}"
+      );
+    }
+
+    assert_eq!(
+      convert_to_base_units("-1", 18).unwrap_err().to_string(),
+      "Malformed number -1: negative amounts are not allowed"
+    );
+  }
+
+  #[test]
+  fn convert_to_base_units_rejects_non_decimal_floats() {
+    for number in ["inf", "NaN", " 1", "0x10"] {
+      assert_eq!(
+        convert_to_base_units(number, 18),
+        Err(Brc20Error::InvalidNumber(number.into())),
+        "{number}"
+      );
+    }
+  }
+
+  #[test]
+  fn convert_to_base_units_is_exact() {
+    assert_eq!(convert_to_base_units("1000", 0), Ok(1000));
+    assert_eq!(convert_to_base_units("1.5", 8), Ok(150_000_000));
+    assert_eq!(convert_to_base_units(".5", 1), Ok(5));
+    assert_eq!(convert_to_base_units("5.", 1), Ok(50));
+    assert_eq!(convert_to_base_units("0.000000000000000001", 18), Ok(1));
+    assert_eq!(
+      convert_to_base_units("20999999999999.999999999999999999", 18),
+      Ok(20_999_999_999_999_999_999_999_999_999_999)
+    );
+  }
+
+  #[test]
+  fn convert_to_base_units_rejects_lone_decimal_point() {
+    assert_eq!(
+      convert_to_base_units(".", 18),
+      Err(Brc20Error::InvalidNumber(".".into()))
+    );
+  }
+
+  #[test]
+  fn convert_to_base_units_rejects_overflow() {
+    let number = "1000000000000000000000";
+    assert_eq!(
+      convert_to_base_units(number, 18),
+      Err(Brc20Error::NumberTooLarge(number.into()))
+    );
+    assert_eq!(
+      Brc20Error::NumberTooLarge(number.into()).to_string(),
+      format!("Number too large: {number}")
+    );
+  }
+
+  #[test]
+  fn format_base_units_round_trips() {
+    for (number, decimals, formatted) in [
+      ("0", 0, "0"),
+      ("21000000", 0, "21000000"),
+      ("0", 8, "0.0"),
+      ("1.50", 8, "1.5"),
+      ("0.00000001", 8, "0.00000001"),
+      ("21000000000000", 18, "21000000000000.0"),
+      (
+        "20999999999999.999999999999999999",
+        18,
+        "20999999999999.999999999999999999",
+      ),
+    ] {
+      assert_eq!(
+        format_base_units(convert_to_base_units(number, decimals).unwrap(), decimals),
+        formatted,
+        "{number}"
+      );
+    }
+  }
+
+  #[test]
+  fn base_units_serialize_as_strings() {
+    #[derive(Serialize)]
+    struct BaseUnits(#[serde(serialize_with = "serialize_base_units")] u128);
+
+    assert_eq!(
+      serde_json::to_value(BaseUnits(u128::MAX)).unwrap(),
+      u128::MAX.to_string()
+    );
+  }
+
+  #[test]
+  fn format_base_units_with_zero_decimals() {
+    assert_eq!(format_base_units(0, 0), "0");
+    assert_eq!(format_base_units(1000, 0), "1000");
+    assert_eq!(format_base_units(21000000, 0), "21000000");
+  }
+
+  #[test]
+  fn format_base_units_with_eight_decimals() {
+    assert_eq!(format_base_units(0, 8), "0.0");
+    assert_eq!(format_base_units(100_000_000, 8), "1.0");
+    assert_eq!(format_base_units(150_000_000, 8), "1.5");
+    assert_eq!(format_base_units(1, 8), "0.00000001");
+    assert_eq!(format_base_units(12_345_678, 8), "0.12345678");
+  }
+
+  #[test]
+  fn format_base_units_with_eighteen_decimals() {
+    assert_eq!(format_base_units(10u128.pow(18), 18), "1.0");
+    assert_eq!(
+      format_base_units(1_000_000_000 * 10u128.pow(18), 18),
+      "1000000000.0"
+    );
+    assert_eq!(format_base_units(25 * 10u128.pow(16), 18), "0.25");
+    assert_eq!(format_base_units(1, 18), "0.000000000000000001");
+  }
+}
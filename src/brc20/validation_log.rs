@@ -0,0 +1,120 @@
+use {super::*, std::io::Write};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ValidationLogEntry {
+  pub(crate) timestamp: String,
+  pub(crate) txid: Txid,
+  pub(crate) op: String,
+  pub(crate) tick: String,
+  pub(crate) valid: bool,
+  pub(crate) reason: Option<String>,
+}
+
+/// Appends a JSON line to `path` for every validated operation. The file is
+/// reopened for each line, so a file moved away by log rotation is replaced
+/// by a new one instead of being written to after the move.
+#[derive(Debug)]
+pub(crate) struct ValidationLog {
+  path: PathBuf,
+}
+
+impl ValidationLog {
+  pub(crate) fn new(path: PathBuf) -> Self {
+    Self { path }
+  }
+
+  pub(crate) fn record(&self, txid: Txid, op: &str, tick: &str, result: Result<(), &Brc20Error>) {
+    let entry = ValidationLogEntry {
+      timestamp: Utc::now().to_rfc3339(),
+      txid,
+      op: op.into(),
+      tick: tick.into(),
+      valid: result.is_ok(),
+      reason: result.err().map(|err| err.to_string()),
+    };
+
+    if let Err(err) = self.write(&entry) {
+      log::warn!(
+        "failed to write validation log {}: {err}",
+        self.path.display()
+      );
+    }
+  }
+
+  fn write(&self, entry: &ValidationLogEntry) -> Result {
+    let mut file = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)?;
+
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+
+    file.write_all(&line)?;
+    file.flush()?;
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entries(path: &Path) -> Vec<ValidationLogEntry> {
+    fs::read_to_string(path)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect()
+  }
+
+  #[test]
+  fn records_are_appended_as_json_lines() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("brc20.log");
+
+    let validation_log = ValidationLog::new(path.clone());
+    validation_log.record(txid(1), "deploy", "ordi", Ok(()));
+    validation_log.record(
+      txid(2),
+      "mint",
+      "ordi",
+      Err(&Brc20Error::MintAmountExceedsLimit),
+    );
+
+    let entries = entries(&path);
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].txid, txid(1));
+    assert_eq!(entries[0].op, "deploy");
+    assert_eq!(entries[0].tick, "ordi");
+    assert!(entries[0].valid);
+    assert_eq!(entries[0].reason, None);
+
+    assert_eq!(entries[1].txid, txid(2));
+    assert!(!entries[1].valid);
+    assert_eq!(
+      entries[1].reason.as_deref(),
+      Some("Mint amount exceeds limit")
+    );
+  }
+
+  #[test]
+  fn rotated_log_file_is_recreated() {
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("brc20.log");
+    let rotated = tempdir.path().join("brc20.log.1");
+
+    let validation_log = ValidationLog::new(path.clone());
+    validation_log.record(txid(1), "deploy", "ordi", Ok(()));
+
+    fs::rename(&path, &rotated).unwrap();
+
+    validation_log.record(txid(2), "mint", "ordi", Ok(()));
+
+    assert_eq!(entries(&rotated).len(), 1);
+    assert_eq!(entries(&path).len(), 1);
+    assert_eq!(entries(&path)[0].txid, txid(2));
+  }
+}
@@ -0,0 +1,291 @@
+use {super::*, reqwest::Url, std::sync::mpsc};
+
+const MAX_ATTEMPTS: u32 = 3;
+
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+const QUEUE_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct WebhookEvent {
+  pub(crate) event: String,
+  pub(crate) ticker: String,
+  pub(crate) amount: String,
+  pub(crate) from: Option<Owner>,
+  pub(crate) to: Option<Owner>,
+  pub(crate) txid: Txid,
+  pub(crate) inscription_id: InscriptionId,
+}
+
+impl WebhookEvent {
+  /// The amount of a deploy is its max supply, and `from` is the deployer.
+  pub(crate) fn deploy(deploy_tx: &Brc20DeployTx) -> Self {
+    Self {
+      event: "deploy".into(),
+      ticker: deploy_tx.get_display_ticker().into(),
+      amount: format_base_units(deploy_tx.get_max_supply(), deploy_tx.get_decimals()),
+      from: Some(deploy_tx.get_brc20_tx().get_owner().clone()),
+      to: None,
+      txid: deploy_tx.get_brc20_tx().get_txid(),
+      inscription_id: deploy_tx.get_inscription_id(),
+    }
+  }
+
+  pub(crate) fn mint(ticker: &Brc20Ticker, mint_tx: &Brc20MintTx) -> Self {
+    Self {
+      event: "mint".into(),
+      ticker: ticker.get_display_ticker().into(),
+      amount: format_base_units(mint_tx.get_amount(), ticker.get_decimals()),
+      from: None,
+      to: Some(mint_tx.get_brc20_tx().get_owner().clone()),
+      txid: mint_tx.get_brc20_tx().get_txid(),
+      inscription_id: mint_tx.get_inscription_id(),
+    }
+  }
+
+  /// Sent transfers have the receiver as `to` and the send transaction as
+  /// `txid`, inscribed ones only have the sender.
+  pub(crate) fn transfer(ticker: &Brc20Ticker, transfer_tx: &Brc20TransferTx) -> Self {
+    Self {
+      event: if transfer_tx.is_completed() {
+        "send"
+      } else {
+        "transfer"
+      }
+      .into(),
+      ticker: ticker.get_display_ticker().into(),
+      amount: format_base_units(transfer_tx.get_amount(), ticker.get_decimals()),
+      from: Some(transfer_tx.get_brc20_tx().get_owner().clone()),
+      to: transfer_tx.get_receiver().cloned(),
+      txid: transfer_tx
+        .get_send_tx()
+        .unwrap_or(transfer_tx.get_brc20_tx())
+        .get_txid(),
+      inscription_id: transfer_tx.get_inscription_id(),
+    }
+  }
+}
+
+/// POSTs every event to `url` as JSON. Events are queued and sent from a
+/// separate thread, so indexing only waits on the webhook once
+/// `QUEUE_CAPACITY` events are queued. Failed requests
+/// are retried, and events that still fail are logged and dropped. Queued
+/// events are sent before the webhook is dropped.
+#[derive(Debug)]
+pub(crate) struct Webhook {
+  sender: Option<mpsc::SyncSender<WebhookEvent>>,
+  worker: Option<thread::JoinHandle<()>>,
+}
+
+impl Webhook {
+  pub(crate) fn new(url: Url) -> Self {
+    let (sender, receiver) = mpsc::sync_channel::<WebhookEvent>(QUEUE_CAPACITY);
+
+    let worker = thread::spawn(move || {
+      let client = reqwest::blocking::Client::new();
+
+      for event in receiver {
+        if let Err(err) = Self::post(&client, &url, &event) {
+          log::warn!(
+            "failed to send {} event to webhook {url}: {err}",
+            event.event
+          );
+        }
+      }
+    });
+
+    Self {
+      sender: Some(sender),
+      worker: Some(worker),
+    }
+  }
+
+  pub(crate) fn notify(&self, event: WebhookEvent) {
+    if let Some(sender) = &self.sender {
+      sender.send(event).ok();
+    }
+  }
+
+  fn post(client: &reqwest::blocking::Client, url: &Url, event: &WebhookEvent) -> Result {
+    let body = serde_json::to_vec(event)?;
+    let mut attempt = 1;
+
+    loop {
+      let result = client
+        .post(url.clone())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.clone())
+        .send()
+        .and_then(|response| response.error_for_status());
+
+      match result {
+        Ok(_) => return Ok(()),
+        Err(err) if attempt >= MAX_ATTEMPTS => return Err(err.into()),
+        Err(err) => {
+          log::debug!("webhook attempt {attempt} failed, retrying: {err}");
+          thread::sleep(RETRY_DELAY * attempt);
+          attempt += 1;
+        }
+      }
+    }
+  }
+}
+
+impl Drop for Webhook {
+  fn drop(&mut self) {
+    self.sender.take();
+
+    if let Some(worker) = self.worker.take() {
+      worker.join().ok();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    std::io::{BufRead, BufReader, Read, Write},
+  };
+
+  /// Accepts requests on a local port, answering the first `failures` with
+  /// a 500, and sends the body of every request to the returned receiver.
+  fn mock_server(failures: usize) -> (Url, mpsc::Receiver<WebhookEvent>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/events", listener.local_addr().unwrap())
+      .parse()
+      .unwrap();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+      for (i, stream) in listener.incoming().enumerate() {
+        let mut reader = BufReader::new(stream.unwrap());
+
+        let mut content_length = 0;
+        loop {
+          let mut line = String::new();
+          reader.read_line(&mut line).unwrap();
+          if line == "\r\n" {
+            break;
+          }
+          if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+              content_length = value.trim().parse().unwrap();
+            }
+          }
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+
+        let status = if i < failures {
+          "500 Internal Server Error"
+        } else {
+          sender.send(serde_json::from_slice(&body).unwrap()).unwrap();
+          "200 OK"
+        };
+
+        write!(
+          reader.get_mut(),
+          "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+      }
+    });
+
+    (url, receiver)
+  }
+
+  #[test]
+  fn valid_operations_are_posted() {
+    let (url, receiver) = mock_server(0);
+
+    let mut brc20_index = Brc20Index::new();
+    brc20_index.send_events_to(Webhook::new(url));
+
+    assert!(brc20_index.process_deploy(
+      inscription_id(1),
+      1,
+      brc20_tx(1, &owner(1), 100),
+      deploy_script(r#"{"p":"brc-20","op":"deploy","tick":"ORDI","max":"1000","lim":"10"}"#)
+        .unwrap(),
+    ));
+    assert!(!brc20_index.process_mint(
+      inscription_id(2),
+      2,
+      brc20_tx(2, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"11"}"#).unwrap(),
+    ));
+    assert!(brc20_index.process_mint(
+      inscription_id(3),
+      3,
+      brc20_tx(3, &owner(2), 200),
+      mint_script(r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"10"}"#).unwrap(),
+    ));
+    assert!(brc20_index.process_transfer(
+      inscription_id(4),
+      4,
+      brc20_tx(4, &owner(2), 300),
+      transfer_script(r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"4"}"#).unwrap(),
+    ));
+    assert!(brc20_index.process_transfer_send(
+      "ordi",
+      &owner(2),
+      &owner(2),
+      inscription_id(4),
+      brc20_tx(5, &owner(3), 400),
+      sat_point(5),
+    ));
+
+    drop(brc20_index);
+
+    let events = receiver.try_iter().collect::<Vec<WebhookEvent>>();
+
+    assert_eq!(
+      events
+        .iter()
+        .map(|event| (event.event.as_str(), event.amount.as_str(), event.txid))
+        .collect::<Vec<(&str, &str, Txid)>>(),
+      [
+        ("deploy", "1000.0", txid(1)),
+        ("mint", "10.0", txid(3)),
+        ("transfer", "4.0", txid(4)),
+        ("send", "4.0", txid(5)),
+      ]
+    );
+
+    assert_eq!(
+      events[3],
+      WebhookEvent {
+        event: "send".into(),
+        ticker: "ORDI".into(),
+        amount: "4.0".into(),
+        from: Some(owner(2)),
+        to: Some(owner(3)),
+        txid: txid(5),
+        inscription_id: inscription_id(4),
+      }
+    );
+  }
+
+  #[test]
+  fn failed_requests_are_retried() {
+    let (url, receiver) = mock_server(1);
+
+    let webhook = Webhook::new(url);
+    webhook.notify(WebhookEvent {
+      event: "mint".into(),
+      ticker: "ordi".into(),
+      amount: "10".into(),
+      from: None,
+      to: Some(owner(1)),
+      txid: txid(1),
+      inscription_id: inscription_id(1),
+    });
+    drop(webhook);
+
+    let events = receiver.try_iter().collect::<Vec<WebhookEvent>>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].txid, txid(1));
+  }
+}
@@ -9,7 +9,10 @@ use {
   super::*,
   crate::wallet::Wallet,
   bitcoin::BlockHeader,
-  bitcoincore_rpc::{json::GetBlockHeaderResult, Client},
+  bitcoincore_rpc::{
+    json::{GetBlockHeaderResult, GetRawTransactionResult},
+    Client,
+  },
   chrono::SubsecRound,
   indicatif::{ProgressBar, ProgressStyle},
   log::log_enabled,
@@ -570,6 +573,16 @@ impl Index {
     }
   }
 
+  pub(crate) fn get_raw_transaction_info(
+    &self,
+    txid: Txid,
+  ) -> Result<Option<GetRawTransactionResult>> {
+    self
+      .client
+      .get_raw_transaction_info(&txid, None)
+      .into_option()
+  }
+
   pub(crate) fn get_transaction_blockhash(&self, txid: Txid) -> Result<Option<BlockHash>> {
     Ok(
       self
@@ -709,6 +722,23 @@ impl Index {
     )
   }
 
+  pub(crate) fn get_inscription_ids_by_number(
+    &self,
+    from: i64,
+    n: usize,
+  ) -> Result<Vec<(i64, InscriptionId)>> {
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(INSCRIPTION_NUMBER_TO_INSCRIPTION_ID)?
+        .range(from..)?
+        .take(n)
+        .map(|(number, id)| (number.value(), Entry::load(*id.value())))
+        .collect(),
+    )
+  }
+
   pub(crate) fn get_homepage_inscriptions(&self) -> Result<Vec<InscriptionId>> {
     Ok(
       self
@@ -2778,4 +2808,50 @@ mod tests {
       );
     }
   }
+
+  #[test]
+  fn inscription_ids_can_be_paged_by_number() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let mut inscription_ids = Vec::new();
+
+      for i in 0..5 {
+        let txid = context.rpc_server.broadcast_tx(TransactionTemplate {
+          inputs: &[(i + 1, 0, 0)],
+          witness: inscription("text/plain", "hello").to_witness(),
+          ..Default::default()
+        });
+        inscription_ids.push(InscriptionId::from(txid));
+        context.mine_blocks(1);
+      }
+
+      let mut paged = Vec::new();
+      let mut from = 0;
+
+      loop {
+        let page = context
+          .index
+          .get_inscription_ids_by_number(from, 2)
+          .unwrap();
+        assert!(page.len() <= 2);
+
+        let Some((number, _)) = page.last() else {
+          break;
+        };
+
+        from = number + 1;
+        paged.extend(page);
+      }
+
+      assert_eq!(
+        paged,
+        inscription_ids
+          .into_iter()
+          .enumerate()
+          .map(|(number, id)| (i64::try_from(number).unwrap(), id))
+          .collect::<Vec<(i64, InscriptionId)>>()
+      );
+    }
+  }
 }
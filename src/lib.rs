@@ -96,6 +96,7 @@ macro_rules! tprintln {
 
 mod arguments;
 mod blocktime;
+mod brc20;
 mod chain;
 mod config;
 mod decimal;
@@ -0,0 +1,263 @@
+use {
+  super::*,
+  crate::brc20::{
+    convert_to_base_units, format_base_units, index_brc20, Brc20Index, Brc20Ticker, IndexerOptions,
+    LocalTxProvider, Metrics, MovementCause, Owner, ProtocolAllowlist, TickerLengthMode,
+    TickerLengthPolicy, TxInfoCache, TxProvider, Webhook,
+  },
+};
+
+pub mod at;
+pub mod audit;
+pub mod balance;
+pub mod compare;
+pub mod diff_blocks;
+pub mod fees;
+pub mod history;
+pub mod index;
+pub mod invalid;
+pub mod list;
+pub mod mint_chart;
+pub mod movements;
+pub mod pending;
+#[cfg(unix)]
+pub mod serve;
+pub mod snapshot;
+pub mod ticker_info;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Brc20 {
+  #[clap(flatten)]
+  index_options: IndexOptions,
+  #[clap(subcommand)]
+  subcommand: Brc20Subcommand,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct IndexOptions {
+  #[clap(
+    long,
+    use_value_delimiter = true,
+    help = "Only index operations on <TICKERS>, separated by commas"
+  )]
+  tickers: Vec<String>,
+  #[clap(
+    long,
+    help = "Append a JSON line for every validated operation to <LOG_FILE>"
+  )]
+  log_file: Option<PathBuf>,
+  #[clap(
+    long,
+    use_value_delimiter = true,
+    default_value = "4",
+    help = "Allow tickers of <TICKER_LENGTHS> bytes, separated by commas"
+  )]
+  ticker_lengths: Vec<usize>,
+  #[clap(
+    long,
+    use_value_delimiter = true,
+    default_value = "5",
+    help = "Also allow self-mint tickers of <SELF_MINT_TICKER_LENGTHS> bytes, separated by commas"
+  )]
+  self_mint_ticker_lengths: Vec<usize>,
+  #[clap(
+    long,
+    value_enum,
+    default_value = "bytes",
+    help = "Measure ticker lengths in <TICKER_LENGTH_MODE>. Changes which historical deploys are valid"
+  )]
+  ticker_length_mode: TickerLengthMode,
+  #[clap(
+    long,
+    use_value_delimiter = true,
+    default_value = "brc-20",
+    help = "Accept <PROTOCOLS> as the `p` of BRC-20 operations, separated by commas"
+  )]
+  protocols: Vec<String>,
+  #[clap(
+    long,
+    default_value_t = 18,
+    value_parser = clap::value_parser!(u8).range(0..=18),
+    help = "Give tickers deployed without `dec` <DEFAULT_DECIMALS> decimals"
+  )]
+  default_decimals: u8,
+  #[clap(long, help = "Record skipped inscriptions and why they were skipped")]
+  record_skipped: bool,
+  #[clap(
+    long,
+    help = "Fail instead of skipping inscriptions that are listed by number but not found by id"
+  )]
+  strict: bool,
+  #[clap(
+    long,
+    default_value_t = 0,
+    help = "Only count inscriptions whose reveal transaction has at least <MIN_CONFIRMATIONS> confirmations"
+  )]
+  min_confirmations: u32,
+  #[clap(
+    long,
+    default_value_t = 0,
+    help = "Log progress every <PROGRESS_INTERVAL> inscriptions. 0 disables progress reports"
+  )]
+  progress_interval: usize,
+  #[clap(
+    long,
+    help = "Read transactions from <RAW_TXS>, a JSON array of getrawtransaction results"
+  )]
+  raw_txs: Option<PathBuf>,
+  #[clap(
+    long,
+    default_value_t = TxInfoCache::DEFAULT_CAPACITY,
+    help = "Cache up to <TX_CACHE_SIZE> raw transactions while indexing. 0 disables caching"
+  )]
+  tx_cache_size: usize,
+  #[clap(
+    long,
+    help = "POST a JSON event for every valid deploy, mint, transfer, and send to <WEBHOOK_URL>"
+  )]
+  webhook_url: Option<reqwest::Url>,
+  #[clap(
+    long,
+    default_value_t = 1,
+    conflicts_with_all = &["log-file", "webhook-url"],
+    help = "Index <SHARDS> ranges of inscriptions in parallel and merge the results"
+  )]
+  shards: usize,
+}
+
+impl IndexOptions {
+  /// Every option at its default, except `tickers` and `min_confirmations`,
+  /// the only ones `ord server --brc20` takes.
+  pub(crate) fn for_server(tickers: Vec<String>, min_confirmations: u32) -> Self {
+    Self {
+      tickers,
+      min_confirmations,
+      ..Self::parse_from(["brc20"])
+    }
+  }
+
+  pub(crate) fn index_brc20(&self, index: &Index, network: Network) -> Result<Brc20Index> {
+    self.index_brc20_with_metrics(index, network, None)
+  }
+
+  pub(crate) fn index_brc20_with_metrics(
+    &self,
+    index: &Index,
+    network: Network,
+    metrics: Option<Arc<Metrics>>,
+  ) -> Result<Brc20Index> {
+    let tickers = (!self.tickers.is_empty()).then(|| {
+      self
+        .tickers
+        .iter()
+        .map(|tick| tick.to_lowercase())
+        .collect::<HashSet<String>>()
+    });
+
+    let local_tx_provider = self
+      .raw_txs
+      .as_deref()
+      .map(LocalTxProvider::load)
+      .transpose()?;
+
+    let tx_provider: &dyn TxProvider = match &local_tx_provider {
+      Some(local_tx_provider) => local_tx_provider,
+      None => index,
+    };
+
+    index_brc20(
+      index,
+      network,
+      IndexerOptions {
+        tickers,
+        log_file: self.log_file.clone(),
+        record_skipped: self.record_skipped,
+        strict: self.strict,
+        min_confirmations: self.min_confirmations,
+        progress_interval: self.progress_interval,
+        ticker_length_policy: TickerLengthPolicy::new(
+          self.ticker_lengths.iter().copied(),
+          self.self_mint_ticker_lengths.iter().copied(),
+          self.ticker_length_mode,
+        ),
+        protocols: ProtocolAllowlist::new(&self.protocols),
+        default_decimals: self.default_decimals,
+        webhook: self.webhook_url.clone().map(Webhook::new),
+        metrics,
+        shards: self.shards,
+      },
+      tx_provider,
+      &TxInfoCache::new(self.tx_cache_size),
+    )
+  }
+}
+
+#[derive(Debug, Parser)]
+enum Brc20Subcommand {
+  #[clap(about = "Display the BRC-20 operation inscribed on a sat")]
+  At(at::At),
+  #[clap(about = "Compare indexed BRC-20 balances against balances recomputed from scratch")]
+  Audit,
+  #[clap(about = "Display BRC-20 balances of an address")]
+  Balance(balance::Balance),
+  #[clap(about = "Compare BRC-20 balances and supply against a reference index")]
+  Compare(compare::Compare),
+  #[clap(about = "Display BRC-20 state changes between two block heights")]
+  DiffBlocks(diff_blocks::DiffBlocks),
+  #[clap(about = "Display inscription size and fee stats of a BRC-20 ticker")]
+  Fees(fees::Fees),
+  #[clap(about = "Display BRC-20 transfer history of an address")]
+  History(history::History),
+  #[clap(about = "Index BRC-20 operations and display a summary")]
+  Index,
+  #[clap(about = "Display rejected BRC-20 operations and why they were rejected")]
+  Invalid(invalid::Invalid),
+  #[clap(about = "List BRC-20 tickers")]
+  List(list::List),
+  #[clap(about = "Display amounts minted per block of a BRC-20 ticker")]
+  MintChart(mint_chart::MintChart),
+  #[clap(about = "Display changes to BRC-20 balances and what caused them")]
+  Movements(movements::Movements),
+  #[clap(about = "Display inscribed BRC-20 transfers that haven't been sent")]
+  Pending(pending::Pending),
+  #[cfg(unix)]
+  #[clap(about = "Answer BRC-20 queries over a Unix socket")]
+  Serve(serve::Serve),
+  #[clap(about = "Export holders of a BRC-20 ticker as CSV")]
+  Snapshot(snapshot::Snapshot),
+  #[clap(about = "Display information about a BRC-20 ticker")]
+  TickerInfo(ticker_info::TickerInfo),
+}
+
+impl Brc20 {
+  pub(crate) fn run(self, options: Options) -> Result {
+    let index_options = &self.index_options;
+
+    match self.subcommand {
+      Brc20Subcommand::At(at) => at.run(options, index_options),
+      Brc20Subcommand::Audit => audit::run(options, index_options),
+      Brc20Subcommand::Balance(balance) => balance.run(options, index_options),
+      Brc20Subcommand::Compare(compare) => compare.run(options, index_options),
+      Brc20Subcommand::DiffBlocks(diff_blocks) => diff_blocks.run(options, index_options),
+      Brc20Subcommand::Fees(fees) => fees.run(options, index_options),
+      Brc20Subcommand::History(history) => history.run(options, index_options),
+      Brc20Subcommand::Index => index::run(options, index_options),
+      Brc20Subcommand::Invalid(invalid) => invalid.run(options, index_options),
+      Brc20Subcommand::List(list) => list.run(options, index_options),
+      Brc20Subcommand::MintChart(mint_chart) => mint_chart.run(options, index_options),
+      Brc20Subcommand::Movements(movements) => movements.run(options, index_options),
+      Brc20Subcommand::Pending(pending) => pending.run(options, index_options),
+      #[cfg(unix)]
+      Brc20Subcommand::Serve(serve) => serve.run(options, index_options),
+      Brc20Subcommand::Snapshot(snapshot) => snapshot.run(options, index_options),
+      Brc20Subcommand::TickerInfo(ticker_info) => ticker_info.run(options, index_options),
+    }
+  }
+}
+
+fn build_index(options: &Options, index_options: &IndexOptions) -> Result<Brc20Index> {
+  let index = Index::open(options)?;
+  index.update()?;
+
+  index_options.index_brc20(&index, options.chain().network())
+}
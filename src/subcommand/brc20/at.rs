@@ -0,0 +1,73 @@
+use {super::*, crate::brc20::InscribedOperation};
+
+#[derive(Debug, Parser)]
+pub(crate) struct At {
+  #[clap(long, help = "Show the BRC-20 operation on the sat at <SATPOINT>")]
+  satpoint: SatPoint,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub satpoint: SatPoint,
+  pub inscription_id: InscriptionId,
+  pub op: String,
+  pub ticker: String,
+  pub amount: String,
+  pub owner: Owner,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub receiver: Option<Owner>,
+}
+
+impl At {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let operation = brc20_index
+      .inscription_at(self.satpoint)
+      .ok_or_else(|| anyhow!("no BRC-20 operation at {}", self.satpoint))?;
+
+    // The amount of a deploy is its max supply.
+    let (op, inscription_id, brc20_tx, tick, amount, receiver) = match operation {
+      InscribedOperation::Deploy(deploy_tx) => (
+        "deploy",
+        deploy_tx.get_inscription_id(),
+        deploy_tx.get_brc20_tx(),
+        deploy_tx.get_ticker(),
+        deploy_tx.get_max_supply(),
+        None,
+      ),
+      InscribedOperation::Mint(mint_tx) => (
+        "mint",
+        mint_tx.get_inscription_id(),
+        mint_tx.get_brc20_tx(),
+        mint_tx.get_ticker(),
+        mint_tx.get_amount(),
+        None,
+      ),
+      InscribedOperation::Transfer(transfer_tx) => (
+        "transfer",
+        transfer_tx.get_inscription_id(),
+        transfer_tx.get_brc20_tx(),
+        transfer_tx.get_ticker(),
+        transfer_tx.get_amount(),
+        transfer_tx.get_receiver().cloned(),
+      ),
+    };
+
+    let ticker = brc20_index
+      .get_ticker(&tick)
+      .ok_or_else(|| anyhow!("ticker {tick} not found"))?;
+
+    print_json(Output {
+      satpoint: self.satpoint,
+      inscription_id,
+      op: op.into(),
+      ticker: ticker.get_display_ticker().into(),
+      amount: format_base_units(amount, ticker.get_decimals()),
+      owner: brc20_tx.get_owner().clone(),
+      receiver,
+    })?;
+
+    Ok(())
+  }
+}
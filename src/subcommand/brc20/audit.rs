@@ -0,0 +1,60 @@
+use {super::*, std::collections::BTreeSet};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Difference {
+  pub ticker: String,
+  pub address: Owner,
+  pub indexed: String,
+  pub recomputed: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub differences: Vec<Difference>,
+}
+
+pub(crate) fn run(options: Options, index_options: &IndexOptions) -> Result {
+  let brc20_index = build_index(&options, index_options)?;
+
+  let mut differences = Vec::new();
+
+  for ticker in brc20_index.tickers() {
+    let mut recomputed = ticker.clone();
+    recomputed.rebuild_balances();
+
+    let decimals = ticker.get_decimals();
+
+    let addresses = ticker
+      .get_balances()
+      .keys()
+      .chain(recomputed.get_balances().keys())
+      .collect::<BTreeSet<&Owner>>();
+
+    for address in addresses {
+      let indexed = ticker
+        .get_user_balance(address)
+        .map(|user_balance| user_balance.get_overall_balance())
+        .unwrap_or_default();
+
+      let recomputed = recomputed
+        .get_user_balance(address)
+        .map(|user_balance| user_balance.get_overall_balance())
+        .unwrap_or_default();
+
+      if indexed != recomputed {
+        differences.push(Difference {
+          ticker: ticker.get_display_ticker().into(),
+          address: address.clone(),
+          indexed: format_base_units(indexed, decimals),
+          recomputed: format_base_units(recomputed, decimals),
+        });
+      }
+    }
+  }
+
+  differences.sort_by(|a, b| a.ticker.cmp(&b.ticker));
+
+  print_json(Output { differences })?;
+
+  Ok(())
+}
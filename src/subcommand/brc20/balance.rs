@@ -0,0 +1,53 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Balance {
+  #[clap(
+    long,
+    help = "Show BRC-20 balances of <ADDRESS>, or of a hex output script that has no address"
+  )]
+  address: Owner,
+  #[clap(long, help = "Show balances as of the block at <AT_HEIGHT>")]
+  at_height: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub address: Owner,
+  pub tickers: BTreeMap<String, String>,
+}
+
+impl Balance {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    if !self.address.is_valid_for_network(options.chain().network()) {
+      bail!(
+        "Address `{}` is not valid for {}",
+        self.address,
+        options.chain()
+      );
+    }
+
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    let brc20_index = index_options.index_brc20(&index, options.chain().network())?;
+
+    print_json(Output {
+      tickers: brc20_index
+        .tickers()
+        .filter_map(|ticker| {
+          let balance = ticker
+            .get_user_balance(&self.address)?
+            .balance_at(self.at_height.unwrap_or(u64::MAX));
+          Some((
+            ticker.get_display_ticker().to_string(),
+            format_base_units(balance, ticker.get_decimals()),
+          ))
+        })
+        .collect(),
+      address: self.address,
+    })?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,171 @@
+use {super::*, std::collections::BTreeSet};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Compare {
+  #[clap(
+    long,
+    help = "Compare against the BRC-20 index JSON dump at <REFERENCE>, e.g. from another indexer"
+  )]
+  reference: PathBuf,
+}
+
+/// The parts of a `Brc20Index` JSON dump that are compared. Other fields are
+/// ignored, so dumps from other indexers only need to match this much of the
+/// schema.
+#[derive(Debug, Deserialize)]
+struct Reference {
+  tickers: BTreeMap<String, ReferenceTicker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReferenceTicker {
+  max_supply: f64,
+  decimals: u8,
+  total_minted: f64,
+  #[serde(default)]
+  balances: BTreeMap<Owner, ReferenceBalance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReferenceBalance {
+  overall_balance: f64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Mismatch {
+  pub ticker: String,
+  pub field: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub address: Option<Owner>,
+  pub computed: Option<String>,
+  pub reference: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub mismatches: Vec<Mismatch>,
+}
+
+impl Compare {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let reference: Reference = serde_json::from_reader(
+      File::open(&self.reference)
+        .with_context(|| format!("failed to open reference `{}`", self.reference.display()))?,
+    )
+    .with_context(|| format!("failed to parse reference `{}`", self.reference.display()))?;
+
+    let reference = reference
+      .tickers
+      .into_iter()
+      .map(|(tick, ticker)| (tick.to_lowercase(), ticker))
+      .collect::<BTreeMap<String, ReferenceTicker>>();
+
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticks = brc20_index
+      .tickers()
+      .map(|ticker| ticker.get_ticker().to_lowercase())
+      .chain(reference.keys().cloned())
+      .collect::<BTreeSet<String>>();
+
+    let mut mismatches = Vec::new();
+
+    for tick in ticks {
+      let computed = brc20_index.get_ticker(&tick);
+      let reference = reference.get(&tick);
+
+      let Some(decimals) = computed
+        .map(Brc20Ticker::get_decimals)
+        .or(reference.map(|ticker| ticker.decimals))
+      else {
+        continue;
+      };
+
+      let display_tick = computed
+        .map(Brc20Ticker::get_display_ticker)
+        .unwrap_or(&tick)
+        .to_string();
+
+      let mut compare =
+        |field: &str, address: Option<&Owner>, computed: Option<u128>, reference: Option<u128>| {
+          let computed = computed.map(|amount| format_base_units(amount, decimals));
+          let reference = reference.map(|amount| format_base_units(amount, decimals));
+
+          if computed != reference {
+            mismatches.push(Mismatch {
+              ticker: display_tick.clone(),
+              field: field.into(),
+              address: address.cloned(),
+              computed,
+              reference,
+            });
+          }
+        };
+
+      compare(
+        "max_supply",
+        None,
+        computed.map(Brc20Ticker::get_max_supply),
+        reference
+          .map(|ticker| reference_base_units(ticker.max_supply, decimals))
+          .transpose()?,
+      );
+
+      compare(
+        "total_minted",
+        None,
+        computed.map(Brc20Ticker::get_total_minted),
+        reference
+          .map(|ticker| reference_base_units(ticker.total_minted, decimals))
+          .transpose()?,
+      );
+
+      let addresses = computed
+        .into_iter()
+        .flat_map(|ticker| ticker.get_balances().keys())
+        .chain(
+          reference
+            .into_iter()
+            .flat_map(|ticker| ticker.balances.keys()),
+        )
+        .collect::<BTreeSet<&Owner>>();
+
+      // A missing balance is zero, as long as the ticker exists.
+      for address in addresses {
+        compare(
+          "balance",
+          Some(address),
+          computed.map(|ticker| {
+            ticker
+              .get_user_balance(address)
+              .map(|user_balance| user_balance.get_overall_balance())
+              .unwrap_or_default()
+          }),
+          reference
+            .map(|ticker| {
+              ticker
+                .balances
+                .get(address)
+                .map(|balance| reference_base_units(balance.overall_balance, decimals))
+                .unwrap_or(Ok(0))
+            })
+            .transpose()?,
+        );
+      }
+    }
+
+    print_json(Output { mismatches })?;
+
+    Ok(())
+  }
+}
+
+/// Reference amounts are rounded to the ticker's precision before being
+/// converted to base units, so that `f64` rounding error in the reference
+/// doesn't count as a mismatch.
+fn reference_base_units(amount: f64, decimals: u8) -> Result<u128> {
+  let precision = usize::from(decimals);
+
+  convert_to_base_units(&format!("{amount:.precision$}"), decimals)
+    .with_context(|| format!("invalid reference amount `{amount}`"))
+}
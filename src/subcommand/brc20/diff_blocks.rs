@@ -0,0 +1,105 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct DiffBlocks {
+  #[clap(long, help = "Compare against the state as of the block at <FROM>")]
+  from: u64,
+  #[clap(long, help = "Show changes up to and including the block at <TO>")]
+  to: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+  pub ticker: String,
+  pub inscription_id: InscriptionId,
+  pub sender: Owner,
+  pub amount: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub from: u64,
+  pub to: u64,
+  pub new_tickers: Vec<String>,
+  pub balance_changes: BTreeMap<String, BTreeMap<String, String>>,
+  pub new_transfers: Vec<Transfer>,
+}
+
+impl DiffBlocks {
+  /// Changes are those of the blocks after `from` up to and including `to`.
+  /// Rather than replaying the index twice, balances at both heights are
+  /// recomputed from the heights of the operations that made them.
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    if self.from > self.to {
+      bail!("--from {} is above --to {}", self.from, self.to);
+    }
+
+    let index = Index::open(&options)?;
+    index.update()?;
+
+    if self.to >= index.block_count()? {
+      bail!("block {} not found", self.to);
+    }
+
+    let (from, to) = (self.from, self.to);
+    let in_range = |height: u64| height > from && height <= to;
+
+    let brc20_index = index_options.index_brc20(&index, options.chain().network())?;
+
+    let mut new_tickers = Vec::new();
+    let mut balance_changes = BTreeMap::new();
+    let mut new_transfers = Vec::new();
+
+    for ticker in brc20_index.tickers() {
+      let display_ticker = ticker.get_display_ticker();
+      let decimals = ticker.get_decimals();
+
+      if in_range(ticker.get_deploy_tx().get_brc20_tx().get_height()) {
+        new_tickers.push(display_ticker.to_string());
+      }
+
+      let changes = ticker
+        .get_balances()
+        .iter()
+        .filter_map(|(address, user_balance)| {
+          let (before, after) = (user_balance.balance_at(from), user_balance.balance_at(to));
+
+          let change = match after.cmp(&before) {
+            cmp::Ordering::Equal => return None,
+            cmp::Ordering::Greater => format_base_units(after - before, decimals),
+            cmp::Ordering::Less => format!("-{}", format_base_units(before - after, decimals)),
+          };
+
+          Some((address.to_string(), change))
+        })
+        .collect::<BTreeMap<String, String>>();
+
+      if !changes.is_empty() {
+        balance_changes.insert(display_ticker.to_string(), changes);
+      }
+
+      new_transfers.extend(
+        ticker
+          .get_transfers()
+          .iter()
+          .filter(|transfer_tx| in_range(transfer_tx.get_brc20_tx().get_height()))
+          .map(|transfer_tx| Transfer {
+            ticker: display_ticker.to_string(),
+            inscription_id: transfer_tx.get_inscription_id(),
+            sender: transfer_tx.get_brc20_tx().get_owner().clone(),
+            amount: format_base_units(transfer_tx.get_amount(), decimals),
+          }),
+      );
+    }
+
+    print_json(Output {
+      from: self.from,
+      to: self.to,
+      new_tickers,
+      balance_changes,
+      new_transfers,
+    })?;
+
+    Ok(())
+  }
+}
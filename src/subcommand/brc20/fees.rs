@@ -0,0 +1,40 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Fees {
+  #[clap(long, help = "Show fee stats of <TICKER>")]
+  ticker: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub ticker: String,
+  pub inscriptions: usize,
+  pub total_size: usize,
+  pub total_fee: u64,
+  pub average_fee_rate: Option<f64>,
+}
+
+impl Fees {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = brc20_index
+      .get_ticker(&self.ticker)
+      .ok_or_else(|| anyhow!("ticker {} not found", self.ticker))?;
+
+    let fee_stats = brc20_index
+      .fee_stats(&self.ticker)
+      .ok_or_else(|| anyhow!("ticker {} not found", self.ticker))?;
+
+    print_json(Output {
+      ticker: ticker.get_display_ticker().into(),
+      inscriptions: fee_stats.inscriptions,
+      total_size: fee_stats.total_size,
+      total_fee: fee_stats.total_fee,
+      average_fee_rate: fee_stats.average_fee_rate,
+    })?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,96 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct History {
+  #[clap(long, help = "Show transfer history of <ADDRESS>")]
+  address: Owner,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+  Send,
+  Receive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+  pub direction: Direction,
+  pub inscription_id: InscriptionId,
+  pub amount: String,
+  pub sat_point: SatPoint,
+  pub from: Owner,
+  pub to: Owner,
+  pub txid: Txid,
+  pub blocktime: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub address: Owner,
+  pub tickers: BTreeMap<String, Vec<Event>>,
+}
+
+impl History {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    if !self.address.is_valid_for_network(options.chain().network()) {
+      bail!(
+        "Address `{}` is not valid for {}",
+        self.address,
+        options.chain()
+      );
+    }
+
+    let brc20_index = build_index(&options, index_options)?;
+
+    let mut tickers = BTreeMap::new();
+
+    for ticker in brc20_index.tickers() {
+      let Some(user_balance) = ticker.get_user_balance(&self.address) else {
+        continue;
+      };
+
+      let sends = user_balance
+        .get_transfer_sends()
+        .iter()
+        .map(|transfer_tx| (Direction::Send, transfer_tx));
+
+      let receives = user_balance
+        .get_transfer_receives()
+        .iter()
+        .map(|transfer_tx| (Direction::Receive, transfer_tx));
+
+      let mut events = sends
+        .chain(receives)
+        .filter_map(|(direction, transfer_tx)| {
+          let send_tx = transfer_tx.get_send_tx()?;
+          Some(Event {
+            direction,
+            inscription_id: transfer_tx.get_inscription_id(),
+            amount: format_base_units(transfer_tx.get_amount(), ticker.get_decimals()),
+            sat_point: transfer_tx.get_sat_point(),
+            from: transfer_tx.get_brc20_tx().get_owner().clone(),
+            to: send_tx.get_owner().clone(),
+            txid: send_tx.get_txid(),
+            blocktime: send_tx.get_blocktime(),
+          })
+        })
+        .collect::<Vec<Event>>();
+
+      if events.is_empty() {
+        continue;
+      }
+
+      events.sort_by_key(|event| event.blocktime);
+
+      tickers.insert(ticker.get_display_ticker().to_string(), events);
+    }
+
+    print_json(Output {
+      address: self.address,
+      tickers,
+    })?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,49 @@
+use super::*;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Skipped {
+  pub inscription_id: InscriptionId,
+  pub reason: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub tickers: usize,
+  pub mints: usize,
+  pub transfers: usize,
+  pub invalid: usize,
+  pub skipped: usize,
+  pub missing: usize,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub skipped_inscriptions: Option<Vec<Skipped>>,
+}
+
+pub(crate) fn run(options: Options, index_options: &IndexOptions) -> Result {
+  let brc20_index = build_index(&options, index_options)?;
+
+  print_json(Output {
+    tickers: brc20_index.ticker_count(),
+    mints: brc20_index
+      .tickers()
+      .map(|ticker| ticker.get_mints().len())
+      .sum(),
+    transfers: brc20_index
+      .tickers()
+      .map(|ticker| ticker.get_transfers().len())
+      .sum(),
+    invalid: brc20_index.get_invalid_tx_map().len(),
+    skipped: brc20_index.get_skipped_count(),
+    missing: brc20_index.get_missing_inscription_count(),
+    skipped_inscriptions: brc20_index.get_skipped().map(|skipped| {
+      skipped
+        .iter()
+        .map(|(inscription_id, reason)| Skipped {
+          inscription_id: *inscription_id,
+          reason: reason.clone(),
+        })
+        .collect()
+    }),
+  })?;
+
+  Ok(())
+}
@@ -0,0 +1,61 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Invalid {
+  #[clap(long, help = "Only show invalid operations on <TICKER>")]
+  ticker: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidTx {
+  pub inscription_id: InscriptionId,
+  pub txid: Txid,
+  pub op: String,
+  pub ticker: String,
+  pub reason: String,
+  pub blocktime: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub invalid: Vec<InvalidTx>,
+}
+
+impl Invalid {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = self.ticker.map(|ticker| ticker.to_lowercase());
+
+    let mut invalid = brc20_index
+      .get_invalid_tx_map()
+      .iter()
+      .filter(|invalid_tx| {
+        ticker
+          .as_ref()
+          .map(|ticker| invalid_tx.get_tick().to_lowercase() == *ticker)
+          .unwrap_or(true)
+      })
+      .map(|invalid_tx| InvalidTx {
+        inscription_id: invalid_tx.get_inscription_id(),
+        txid: invalid_tx.get_brc20_tx().get_txid(),
+        op: invalid_tx.get_op().into(),
+        ticker: invalid_tx.get_tick().into(),
+        reason: invalid_tx.get_reason().into(),
+        blocktime: invalid_tx.get_brc20_tx().get_blocktime(),
+      })
+      .collect::<Vec<InvalidTx>>();
+
+    invalid.sort_by_key(|invalid_tx| {
+      (
+        invalid_tx.blocktime,
+        invalid_tx.inscription_id.txid,
+        invalid_tx.inscription_id.index,
+      )
+    });
+
+    print_json(Output { invalid })?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,64 @@
+use {super::*, clap::ValueEnum, std::cmp::Reverse};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum Sort {
+  PercentMinted,
+  Holders,
+  Deploy,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct List {
+  #[clap(
+    long,
+    value_enum,
+    default_value = "percent-minted",
+    help = "Sort tickers by <SORT>"
+  )]
+  sort: Sort,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub ticker: String,
+  pub max_supply: String,
+  pub total_minted: String,
+  pub percent_minted: f64,
+  pub holders: usize,
+  pub deploy_blocktime: u64,
+}
+
+impl List {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let summaries = match self.sort {
+      Sort::PercentMinted => brc20_index.list_tickers(),
+      Sort::Holders => {
+        let mut summaries = brc20_index.list_tickers();
+        summaries.sort_by_key(|summary| Reverse(summary.holders));
+        summaries
+      }
+      Sort::Deploy => brc20_index
+        .tickers()
+        .map(|ticker| ticker.summary())
+        .collect(),
+    };
+
+    print_json(
+      summaries
+        .into_iter()
+        .map(|summary| Output {
+          max_supply: format_base_units(summary.max_supply, summary.decimals),
+          total_minted: format_base_units(summary.total_minted, summary.decimals),
+          ticker: summary.ticker,
+          percent_minted: summary.percent_minted,
+          holders: summary.holders,
+          deploy_blocktime: summary.deploy_blocktime,
+        })
+        .collect::<Vec<Output>>(),
+    )?;
+
+    Ok(())
+  }
+}
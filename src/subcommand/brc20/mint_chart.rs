@@ -0,0 +1,47 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct MintChart {
+  #[clap(long, help = "Chart mints of <TICKER>")]
+  ticker: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+  pub blocktime: u64,
+  pub minted: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub ticker: String,
+  pub mints: Vec<Point>,
+}
+
+impl MintChart {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = brc20_index
+      .get_ticker(&self.ticker)
+      .ok_or_else(|| anyhow!("ticker {} not found", self.ticker))?;
+
+    let mut mints = ticker
+      .mints_per_block()
+      .into_iter()
+      .map(|(blocktime, minted)| Point {
+        blocktime,
+        minted: format_base_units(minted, ticker.get_decimals()),
+      })
+      .collect::<Vec<Point>>();
+
+    mints.sort_by_key(|point| point.blocktime);
+
+    print_json(Output {
+      ticker: ticker.get_display_ticker().into(),
+      mints,
+    })?;
+
+    Ok(())
+  }
+}
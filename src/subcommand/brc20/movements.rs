@@ -0,0 +1,81 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Movements {
+  #[clap(long, help = "Only show movements of <ADDRESS>")]
+  address: Option<Owner>,
+  #[clap(long, help = "Only show movements of <TICKER>")]
+  ticker: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cause {
+  Mint,
+  Send,
+  Receive,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Movement {
+  pub address: Owner,
+  pub ticker: String,
+  pub delta: String,
+  pub new_balance: String,
+  pub cause: Cause,
+  pub txid: Txid,
+  pub blocktime: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub movements: Vec<Movement>,
+}
+
+impl Movements {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = self.ticker.map(|ticker| ticker.to_lowercase());
+
+    let movements = brc20_index
+      .movements()
+      .into_iter()
+      .filter(|movement| {
+        self
+          .address
+          .as_ref()
+          .map(|address| movement.address == *address)
+          .unwrap_or(true)
+          && ticker
+            .as_ref()
+            .map(|ticker| movement.ticker.to_lowercase() == *ticker)
+            .unwrap_or(true)
+      })
+      .map(|movement| {
+        let amount = format_base_units(movement.amount, movement.decimals);
+
+        Movement {
+          address: movement.address,
+          ticker: movement.ticker,
+          delta: match movement.cause {
+            MovementCause::Send => format!("-{amount}"),
+            MovementCause::Mint | MovementCause::Receive => amount,
+          },
+          new_balance: format_base_units(movement.new_balance, movement.decimals),
+          cause: match movement.cause {
+            MovementCause::Mint => Cause::Mint,
+            MovementCause::Send => Cause::Send,
+            MovementCause::Receive => Cause::Receive,
+          },
+          txid: movement.txid,
+          blocktime: movement.blocktime,
+        }
+      })
+      .collect();
+
+    print_json(Output { movements })?;
+
+    Ok(())
+  }
+}
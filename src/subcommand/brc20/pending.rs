@@ -0,0 +1,47 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct Pending {
+  #[clap(long, help = "Show pending transfers of <TICKER>")]
+  ticker: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+  pub inscription_id: InscriptionId,
+  pub owner: Owner,
+  pub amount: String,
+  pub blocktime: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub ticker: String,
+  pub transfers: Vec<Transfer>,
+}
+
+impl Pending {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = brc20_index
+      .get_ticker(&self.ticker)
+      .ok_or_else(|| anyhow!("ticker {} not found", self.ticker))?;
+
+    print_json(Output {
+      ticker: ticker.get_display_ticker().into(),
+      transfers: ticker
+        .get_all_active_transfers()
+        .into_iter()
+        .map(|transfer_tx| Transfer {
+          inscription_id: transfer_tx.get_inscription_id(),
+          owner: transfer_tx.get_brc20_tx().get_owner().clone(),
+          amount: format_base_units(transfer_tx.get_amount(), ticker.get_decimals()),
+          blocktime: transfer_tx.get_brc20_tx().get_blocktime(),
+        })
+        .collect(),
+    })?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,145 @@
+use {
+  super::*,
+  std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+  },
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Serve {
+  #[clap(long, help = "Listen for requests on Unix socket <SOCKET>")]
+  socket: PathBuf,
+}
+
+/// A request, sent as one line of JSON, for example
+/// `{"method":"balance","params":{"address":"bc1…","ticker":"ordi"}}`.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "kebab-case")]
+pub enum Request {
+  Balance { address: Owner, ticker: String },
+  Holders { ticker: String },
+  TickerInfo { ticker: String },
+}
+
+/// A response, sent back as one line of JSON.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Response {
+  Result(serde_json::Value),
+  Error(String),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Balance {
+  pub address: Owner,
+  pub ticker: String,
+  pub overall: String,
+  pub available: String,
+  pub transferable: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Holder {
+  pub address: Owner,
+  pub balance: String,
+}
+
+impl Serve {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    if self.socket.exists() {
+      fs::remove_file(&self.socket)?;
+    }
+
+    let listener = UnixListener::bind(&self.socket)
+      .with_context(|| format!("failed to bind {}", self.socket.display()))?;
+
+    eprintln!("Listening on {}", self.socket.display());
+
+    for stream in listener.incoming() {
+      if let Err(err) = handle_connection(&brc20_index, stream?) {
+        log::warn!("connection error: {err}");
+      }
+    }
+
+    Ok(())
+  }
+}
+
+fn handle_connection(brc20_index: &Brc20Index, stream: UnixStream) -> Result {
+  let mut writer = stream.try_clone()?;
+
+  for line in BufReader::new(stream).lines() {
+    let response = match serde_json::from_str::<Request>(&line?) {
+      Ok(request) => match handle(brc20_index, request) {
+        Ok(result) => Response::Result(result),
+        Err(err) => Response::Error(err.to_string()),
+      },
+      Err(err) => Response::Error(format!("invalid request: {err}")),
+    };
+
+    let mut line = serde_json::to_vec(&response)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    writer.flush()?;
+  }
+
+  Ok(())
+}
+
+fn handle(brc20_index: &Brc20Index, request: Request) -> Result<serde_json::Value> {
+  let get_ticker = |tick: &str| {
+    brc20_index
+      .get_ticker(tick)
+      .ok_or_else(|| anyhow!("ticker {tick} not found"))
+  };
+
+  Ok(match request {
+    Request::Balance { address, ticker } => {
+      let ticker = get_ticker(&ticker)?;
+      let decimals = ticker.get_decimals();
+      let user_balance = ticker
+        .get_user_balance(&address)
+        .cloned()
+        .unwrap_or_default();
+
+      serde_json::to_value(Balance {
+        overall: format_base_units(user_balance.get_overall_balance(), decimals),
+        available: format_base_units(user_balance.get_available_balance(), decimals),
+        transferable: format_base_units(user_balance.get_transferable_balance(), decimals),
+        ticker: ticker.get_display_ticker().into(),
+        address,
+      })?
+    }
+    Request::Holders { ticker } => {
+      let ticker = get_ticker(&ticker)?;
+
+      let mut holders = ticker
+        .get_balances()
+        .iter()
+        .filter(|(_, user_balance)| user_balance.get_overall_balance() > 0)
+        .collect::<Vec<_>>();
+
+      holders.sort_by(|(a_address, a), (b_address, b)| {
+        b.get_overall_balance()
+          .cmp(&a.get_overall_balance())
+          .then_with(|| a_address.to_string().cmp(&b_address.to_string()))
+      });
+
+      serde_json::to_value(
+        holders
+          .into_iter()
+          .map(|(address, user_balance)| Holder {
+            address: address.clone(),
+            balance: format_base_units(user_balance.get_overall_balance(), ticker.get_decimals()),
+          })
+          .collect::<Vec<Holder>>(),
+      )?
+    }
+    Request::TickerInfo { ticker } => {
+      serde_json::to_value(ticker_info::Output::new(get_ticker(&ticker)?))?
+    }
+  })
+}
@@ -0,0 +1,73 @@
+use {
+  super::*,
+  std::io::{BufWriter, Write},
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Snapshot {
+  #[clap(long, help = "Export holders of <TICKER>")]
+  ticker: String,
+  #[clap(long, help = "Write holders as CSV to <OUTPUT>")]
+  output: PathBuf,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub ticker: String,
+  pub holders: usize,
+}
+
+impl Snapshot {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = brc20_index
+      .get_ticker(&self.ticker)
+      .ok_or_else(|| anyhow!("ticker {} not found", self.ticker))?;
+
+    let decimals = ticker.get_decimals();
+
+    let mut holders = ticker
+      .get_balances()
+      .iter()
+      .filter(|(_, user_balance)| user_balance.get_overall_balance() > 0)
+      .collect::<Vec<_>>();
+
+    holders.sort_by(|(a_address, a), (b_address, b)| {
+      b.get_overall_balance()
+        .cmp(&a.get_overall_balance())
+        .then_with(|| a_address.to_string().cmp(&b_address.to_string()))
+    });
+
+    // Rows are written as they're formatted, instead of building the whole
+    // file in memory. Addresses and amounts never need quoting.
+    let mut writer = BufWriter::new(
+      File::create(&self.output)
+        .with_context(|| format!("failed to create {}", self.output.display()))?,
+    );
+
+    writeln!(
+      writer,
+      "address,overall_balance,available_balance,transferable_balance"
+    )?;
+
+    for (address, user_balance) in &holders {
+      writeln!(
+        writer,
+        "{address},{},{},{}",
+        format_base_units(user_balance.get_overall_balance(), decimals),
+        format_base_units(user_balance.get_available_balance(), decimals),
+        format_base_units(user_balance.get_transferable_balance(), decimals),
+      )?;
+    }
+
+    writer.flush()?;
+
+    print_json(Output {
+      ticker: ticker.get_display_ticker().into(),
+      holders: holders.len(),
+    })?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+#[derive(Debug, Parser)]
+pub(crate) struct TickerInfo {
+  #[clap(long, help = "Show information about <TICKER>")]
+  ticker: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Output {
+  pub ticker: String,
+  pub max_supply: String,
+  pub limit: String,
+  pub decimals: u8,
+  pub deploy_inscription: InscriptionId,
+  pub deploy_inscription_number: i64,
+  pub raw: String,
+  pub holders: usize,
+  pub total_minted: String,
+  pub circulating_supply: String,
+  pub transferred: String,
+  pub active_transfers: usize,
+  pub first_mint_blocktime: Option<u64>,
+  pub last_mint_blocktime: Option<u64>,
+  pub holders_by_address_type: BTreeMap<String, usize>,
+}
+
+impl Output {
+  pub(crate) fn new(ticker: &Brc20Ticker) -> Self {
+    let decimals = ticker.get_decimals();
+    let stats = ticker.stats();
+
+    Self {
+      ticker: ticker.get_display_ticker().into(),
+      max_supply: format_base_units(ticker.get_max_supply(), decimals),
+      limit: format_base_units(ticker.get_limit(), decimals),
+      decimals,
+      deploy_inscription: ticker.get_deploy_tx().get_inscription_id(),
+      deploy_inscription_number: ticker.get_deploy_tx().get_inscription_number(),
+      raw: ticker.get_deploy_tx().get_raw_body().into(),
+      holders: stats.holders,
+      total_minted: format_base_units(ticker.get_total_minted(), decimals),
+      circulating_supply: format_base_units(stats.circulating_supply, decimals),
+      transferred: format_base_units(stats.transferred, decimals),
+      active_transfers: stats.active_transfers,
+      first_mint_blocktime: stats.mint_time_range.map(|(first, _)| first),
+      last_mint_blocktime: stats.mint_time_range.map(|(_, last)| last),
+      holders_by_address_type: stats
+        .holders_by_address_type
+        .into_iter()
+        .map(|(address_type, holders)| (address_type.to_string(), holders))
+        .collect(),
+    }
+  }
+}
+
+impl TickerInfo {
+  pub(crate) fn run(self, options: Options, index_options: &IndexOptions) -> Result {
+    let brc20_index = build_index(&options, index_options)?;
+
+    let ticker = brc20_index
+      .get_ticker(&self.ticker)
+      .ok_or_else(|| anyhow!("ticker {} not found", self.ticker))?;
+
+    print_json(Output::new(ticker))?;
+
+    Ok(())
+  }
+}
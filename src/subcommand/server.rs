@@ -4,6 +4,7 @@ use {
     error::{OptionExt, ServerError, ServerResult},
   },
   super::*,
+  crate::brc20::{Brc20Index, Metrics, RecentOperation},
   crate::page_config::PageConfig,
   crate::templates::{
     BlockHtml, ClockSvg, HomeHtml, InputHtml, InscriptionHtml, InscriptionsHtml, OutputHtml,
@@ -17,7 +18,7 @@ use {
     http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
     response::{IntoResponse, Redirect, Response},
     routing::get,
-    Router, TypedHeader,
+    Json, Router, TypedHeader,
   },
   axum_server::Handle,
   rust_embed::RustEmbed,
@@ -27,7 +28,7 @@ use {
     caches::DirCache,
     AcmeConfig,
   },
-  std::{cmp::Ordering, str},
+  std::{cmp::Ordering, str, sync::RwLock},
   tokio_stream::StreamExt,
   tower_http::{
     compression::CompressionLayer,
@@ -66,6 +67,11 @@ struct Search {
   query: String,
 }
 
+#[derive(Deserialize)]
+struct Recent {
+  limit: Option<usize>,
+}
+
 #[derive(RustEmbed)]
 #[folder = "static"]
 struct StaticAssets;
@@ -121,10 +127,104 @@ pub(crate) struct Server {
   https: bool,
   #[clap(long, help = "Redirect HTTP traffic to HTTPS.")]
   redirect_http_to_https: bool,
+  #[clap(
+    long,
+    help = "Serve BRC-20 operations under /brc20/, reindexed whenever new blocks are indexed."
+  )]
+  brc20: bool,
+  #[clap(
+    long,
+    requires = "brc20",
+    use_value_delimiter = true,
+    help = "Only serve BRC-20 operations on <BRC20_TICKERS>, separated by commas."
+  )]
+  brc20_tickers: Vec<String>,
+  #[clap(
+    long,
+    requires = "brc20",
+    help = "Only serve BRC-20 operations whose reveal transaction has at least <BRC20_MIN_CONFIRMATIONS> confirmations."
+  )]
+  brc20_min_confirmations: Option<u32>,
+}
+
+/// Rebuilds the BRC-20 index served under /brc20/ from scratch whenever the
+/// ord index has indexed new blocks. Rebuilds run on their own thread and the
+/// served index is only swapped once a rebuild is done, so neither requests
+/// nor ord index updates wait on them. An empty index is served until the
+/// first rebuild is done. Rebuilds are recorded in `metrics`, served under
+/// /metrics.
+struct Brc20Updater {
+  index_options: super::brc20::IndexOptions,
+  network: Network,
+  block_count: Option<u64>,
+  brc20_index: Arc<RwLock<Brc20Index>>,
+  metrics: Arc<Metrics>,
+}
+
+impl Brc20Updater {
+  fn new(index_options: super::brc20::IndexOptions, network: Network) -> Self {
+    Self {
+      index_options,
+      network,
+      block_count: None,
+      brc20_index: Arc::new(RwLock::new(Brc20Index::new())),
+      metrics: Arc::new(Metrics::default()),
+    }
+  }
+
+  fn update(&mut self, index: &Index) -> Result {
+    let block_count = index.block_count()?;
+
+    if self.block_count == Some(block_count) {
+      return Ok(());
+    }
+
+    let brc20_index = self.index_options.index_brc20_with_metrics(
+      index,
+      self.network,
+      Some(self.metrics.clone()),
+    )?;
+    *self.brc20_index.write().unwrap() = brc20_index;
+    self.block_count = Some(block_count);
+
+    Ok(())
+  }
+
+  fn spawn(mut self, index: Arc<Index>) -> Arc<RwLock<Brc20Index>> {
+    let brc20_index = self.brc20_index.clone();
+
+    thread::spawn(move || loop {
+      if let Err(error) = self.update(&index) {
+        log::warn!("{error}");
+      }
+      thread::sleep(Duration::from_millis(5000));
+    });
+
+    brc20_index
+  }
 }
 
 impl Server {
+  const DEFAULT_RECENT_LIMIT: usize = 100;
+  const MAX_RECENT_LIMIT: usize = 1000;
+
   pub(crate) fn run(self, options: Options, index: Arc<Index>, handle: Handle) -> Result {
+    let brc20_updater = self.brc20.then(|| {
+      Brc20Updater::new(
+        super::brc20::IndexOptions::for_server(
+          self.brc20_tickers.clone(),
+          self.brc20_min_confirmations.unwrap_or_default(),
+        ),
+        options.chain().network(),
+      )
+    });
+
+    let metrics = brc20_updater
+      .as_ref()
+      .map(|brc20_updater| brc20_updater.metrics.clone());
+
+    let brc20_index = brc20_updater.map(|brc20_updater| brc20_updater.spawn(index.clone()));
+
     Runtime::new()?.block_on(async {
       let clone = index.clone();
       thread::spawn(move || loop {
@@ -146,6 +246,7 @@ impl Server {
         .route("/", get(Self::home))
         .route("/block-count", get(Self::block_count))
         .route("/block/:query", get(Self::block))
+        .route("/brc20/recent", get(Self::brc20_recent))
         .route("/bounties", get(Self::bounties))
         .route("/clock", get(Self::clock))
         .route("/content/:inscription_id", get(Self::content))
@@ -157,6 +258,7 @@ impl Server {
         .route("/inscriptions", get(Self::inscriptions))
         .route("/inscriptions/:from", get(Self::inscriptions_from))
         .route("/install.sh", get(Self::install_script))
+        .route("/metrics", get(Self::metrics))
         .route("/ordinal/:sat", get(Self::ordinal))
         .route("/output/:output", get(Self::output))
         .route("/preview/:inscription_id", get(Self::preview))
@@ -169,6 +271,8 @@ impl Server {
         .route("/status", get(Self::status))
         .route("/tx/:txid", get(Self::transaction))
         .layer(Extension(index))
+        .layer(Extension(brc20_index))
+        .layer(Extension(metrics))
         .layer(Extension(page_config))
         .layer(Extension(Arc::new(config)))
         .layer(SetResponseHeaderLayer::if_not_present(
@@ -678,6 +782,35 @@ impl Server {
     Ok(index.block_count()?.to_string())
   }
 
+  async fn brc20_recent(
+    Extension(brc20_index): Extension<Option<Arc<RwLock<Brc20Index>>>>,
+    Query(recent): Query<Recent>,
+  ) -> ServerResult<Json<Vec<RecentOperation>>> {
+    let brc20_index = brc20_index.ok_or_not_found(|| "BRC-20 index")?;
+
+    let limit = match recent.limit {
+      Some(0) => return Err(ServerError::BadRequest("limit must be positive".into())),
+      Some(limit) => limit.min(Self::MAX_RECENT_LIMIT),
+      None => Self::DEFAULT_RECENT_LIMIT,
+    };
+
+    let recent_operations = brc20_index.read().unwrap().recent_operations(limit);
+
+    Ok(Json(recent_operations))
+  }
+
+  async fn metrics(Extension(metrics): Extension<Option<Arc<Metrics>>>) -> ServerResult<Response> {
+    let metrics = metrics.ok_or_not_found(|| "BRC-20 index")?;
+
+    Ok(
+      (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+      )
+        .into_response(),
+    )
+  }
+
   async fn input(
     Extension(page_config): Extension<Arc<PageConfig>>,
     Extension(index): Extension<Arc<Index>>,
@@ -1413,6 +1546,127 @@ mod tests {
     assert_eq!(response.text().unwrap(), "2");
   }
 
+  #[test]
+  fn brc20_recent_without_operations() {
+    TestServer::new_with_args(&[], &["--brc20"]).assert_response(
+      "/brc20/recent?limit=10",
+      StatusCode::OK,
+      "[]",
+    );
+  }
+
+  #[test]
+  fn brc20_recent_with_zero_limit_returns_400() {
+    TestServer::new_with_args(&[], &["--brc20"]).assert_response(
+      "/brc20/recent?limit=0",
+      StatusCode::BAD_REQUEST,
+      "limit must be positive",
+    );
+  }
+
+  #[test]
+  fn brc20_index_is_rebuilt_when_blocks_are_indexed() {
+    let server = TestServer::new_with_regtest();
+    server.mine_blocks(1);
+
+    let mut brc20_updater = Brc20Updater::new(
+      super::super::brc20::IndexOptions::for_server(Vec::new(), 0),
+      Network::Regtest,
+    );
+    brc20_updater.update(&server.index).unwrap();
+    assert_eq!(brc20_updater.brc20_index.read().unwrap().ticker_count(), 0);
+
+    server.bitcoin_rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription(
+        "text/plain;charset=utf-8",
+        r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#,
+      )
+      .to_witness(),
+      ..Default::default()
+    });
+    server.mine_blocks(1);
+
+    brc20_updater.update(&server.index).unwrap();
+    assert_eq!(brc20_updater.brc20_index.read().unwrap().ticker_count(), 1);
+
+    let metrics = brc20_updater.metrics.render();
+    assert!(metrics.contains("\nbrc20_deploys 1\n"));
+    assert!(metrics.contains("\nbrc20_last_successful_height 2\n"));
+  }
+
+  #[test]
+  fn brc20_index_is_rebuilt_in_the_background() {
+    let server = TestServer::new_with_regtest();
+    server.mine_blocks(1);
+
+    server.bitcoin_rpc_server.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0)],
+      witness: inscription(
+        "text/plain;charset=utf-8",
+        r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000"}"#,
+      )
+      .to_witness(),
+      ..Default::default()
+    });
+    server.mine_blocks(1);
+
+    let brc20_index = Brc20Updater::new(
+      super::super::brc20::IndexOptions::for_server(Vec::new(), 0),
+      Network::Regtest,
+    )
+    .spawn(server.index.clone());
+
+    for _ in 0..100 {
+      if brc20_index.read().unwrap().ticker_count() == 1 {
+        return;
+      }
+      thread::sleep(Duration::from_millis(100));
+    }
+
+    panic!("BRC-20 index wasn't rebuilt");
+  }
+
+  #[test]
+  fn brc20_options_require_brc20() {
+    assert!(Arguments::try_parse_from(["ord", "server", "--brc20-tickers", "ordi"]).is_err());
+    assert!(
+      Arguments::try_parse_from(["ord", "server", "--brc20-min-confirmations", "6"]).is_err()
+    );
+
+    let (_, server) = parse_server_args("ord server --brc20 --brc20-tickers ordi,pepe");
+    assert_eq!(server.brc20_tickers, ["ordi", "pepe"]);
+  }
+
+  #[test]
+  fn metrics() {
+    let response = TestServer::new_with_args(&[], &["--brc20"]).get("/metrics");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_TYPE).unwrap(),
+      "text/plain; version=0.0.4"
+    );
+    assert!(response
+      .text()
+      .unwrap()
+      .contains("# TYPE brc20_inscriptions_processed_total counter\n"));
+  }
+
+  #[test]
+  fn metrics_without_brc20_index_returns_404() {
+    TestServer::new().assert_response("/metrics", StatusCode::NOT_FOUND, "BRC-20 index not found");
+  }
+
+  #[test]
+  fn brc20_recent_without_brc20_index_returns_404() {
+    TestServer::new().assert_response(
+      "/brc20/recent",
+      StatusCode::NOT_FOUND,
+      "BRC-20 index not found",
+    );
+  }
+
   #[test]
   fn range_end_before_range_start_returns_400() {
     TestServer::new().assert_response(
@@ -1778,14 +2032,19 @@ mod tests {
   fn transaction() {
     let test_server = TestServer::new();
 
-    let coinbase_tx = test_server.mine_blocks(1)[0].txdata[0].clone();
-    let txid = coinbase_tx.txid();
+    let block = test_server.mine_blocks(1)[0].clone();
+    let blockhash = block.block_hash();
+    let txid = block.txdata[0].txid();
 
     test_server.assert_response_regex(
       format!("/tx/{txid}"),
       StatusCode::OK,
       format!(
         ".*<title>Transaction {txid}</title>.*<h1>Transaction <span class=monospace>{txid}</span></h1>
+<dl>
+  <dt>block</dt>
+  <dd><a href=/block/{blockhash} class=monospace>{blockhash}</a></dd>
+</dl>
 <h2>1 Input</h2>
 <ul>
   <li><a class=monospace href=/output/0000000000000000000000000000000000000000000000000000000000000000:4294967295>0000000000000000000000000000000000000000000000000000000000000000:4294967295</a></li>
@@ -344,26 +344,74 @@ impl Api for Server {
   ) -> Result<Value, jsonrpc_core::Error> {
     assert_eq!(blockhash, None, "Blockhash param is unsupported");
     if verbose.unwrap_or(false) {
-      match self.state().transactions.get(&txid) {
-        Some(_) => Ok(
-          serde_json::to_value(GetRawTransactionResult {
+      let state = self.state();
+
+      match state.transactions.get(&txid) {
+        Some(tx) => {
+          let block = state
+            .hashes
+            .iter()
+            .map(|hash| &state.blocks[hash])
+            .find(|block| block.txdata.contains(tx));
+
+          let mut value = serde_json::to_value(GetRawTransactionResult {
             in_active_chain: Some(true),
             hex: Vec::new(),
-            txid: Txid::all_zeros(),
+            txid,
             hash: Wtxid::all_zeros(),
             size: 0,
             vsize: 0,
             version: 0,
             locktime: 0,
-            vin: Vec::new(),
-            vout: Vec::new(),
-            blockhash: None,
+            vin: tx
+              .input
+              .iter()
+              .map(|input| {
+                let coinbase = input.previous_output.is_null();
+                GetRawTransactionResultVin {
+                  sequence: input.sequence.0,
+                  coinbase: coinbase.then(|| input.script_sig.to_bytes()),
+                  txid: (!coinbase).then_some(input.previous_output.txid),
+                  vout: (!coinbase).then_some(input.previous_output.vout),
+                  script_sig: None,
+                  txinwitness: None,
+                }
+              })
+              .collect(),
+            vout: tx
+              .output
+              .iter()
+              .enumerate()
+              .map(|(n, output)| GetRawTransactionResultVout {
+                value: Amount::from_sat(output.value),
+                n: n.try_into().unwrap(),
+                script_pub_key: GetRawTransactionResultVoutScriptPubKey {
+                  asm: output.script_pubkey.asm(),
+                  hex: output.script_pubkey.to_bytes(),
+                  req_sigs: None,
+                  type_: Some(script_pubkey_type(&output.script_pubkey)),
+                  addresses: Vec::new(),
+                  address: Address::from_script(&output.script_pubkey, self.network).ok(),
+                },
+              })
+              .collect(),
+            blockhash: block.map(|block| block.block_hash()),
             confirmations: Some(1),
-            time: None,
-            blocktime: None,
+            time: block.map(|block| block.header.time.try_into().unwrap()),
+            blocktime: block.map(|block| block.header.time.try_into().unwrap()),
           })
-          .unwrap(),
-        ),
+          .unwrap();
+
+          // Bitcoin Core omits absent input fields, which don't deserialize from `null`
+          for input in value["vin"].as_array_mut().unwrap() {
+            input
+              .as_object_mut()
+              .unwrap()
+              .retain(|_, value| !value.is_null());
+          }
+
+          Ok(value)
+        }
         None => Err(Self::not_found()),
       }
     } else {
@@ -590,3 +638,24 @@ impl Api for Server {
     )
   }
 }
+
+/// The type Bitcoin Core reports for `script`.
+fn script_pubkey_type(script: &Script) -> ScriptPubkeyType {
+  if script.is_p2pk() {
+    ScriptPubkeyType::Pubkey
+  } else if script.is_p2pkh() {
+    ScriptPubkeyType::PubkeyHash
+  } else if script.is_p2sh() {
+    ScriptPubkeyType::ScriptHash
+  } else if script.is_v0_p2wpkh() {
+    ScriptPubkeyType::Witness_v0_KeyHash
+  } else if script.is_v0_p2wsh() {
+    ScriptPubkeyType::Witness_v0_ScriptHash
+  } else if script.is_v1_p2tr() {
+    ScriptPubkeyType::Witness_v1_Taproot
+  } else if script.is_op_return() {
+    ScriptPubkeyType::NullData
+  } else {
+    ScriptPubkeyType::Nonstandard
+  }
+}
@@ -0,0 +1,1320 @@
+use {
+  super::*,
+  ord::subcommand::brc20::{
+    at, audit, balance,
+    compare::{self, Mismatch},
+    diff_blocks, fees,
+    history::{self, Direction, Event},
+    index::Output,
+    invalid::{self, InvalidTx},
+    list, mint_chart,
+    movements::{self, Cause, Movement},
+    pending, snapshot, ticker_info,
+  },
+};
+
+const DESTINATION: &str = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+
+const RECEIVER: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+fn inscribe_json(rpc_server: &test_bitcoincore_rpc::Handle, json: &str) -> Inscribe {
+  rpc_server.mine_blocks(1);
+
+  let inscribe = CommandBuilder::new(format!(
+    "wallet inscribe --fee-rate 1 --destination {DESTINATION} brc20.json"
+  ))
+  .write("brc20.json", json)
+  .rpc_server(rpc_server)
+  .output::<Inscribe>();
+
+  rpc_server.mine_blocks(1);
+
+  inscribe
+}
+
+#[test]
+fn index_without_inscriptions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  assert_eq!(
+    CommandBuilder::new("brc20 index")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      tickers: 0,
+      mints: 0,
+      transfers: 0,
+      invalid: 0,
+      skipped: 0,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+}
+
+#[test]
+fn index_deploy_mint_and_transfer() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1001"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+  inscribe(&rpc_server);
+
+  assert_eq!(
+    CommandBuilder::new("brc20 index")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      tickers: 1,
+      mints: 1,
+      transfers: 1,
+      invalid: 1,
+      skipped: 1,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+}
+
+#[test]
+fn record_skipped_inscriptions() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  rpc_server.mine_blocks(1);
+
+  let inscribe = CommandBuilder::new("wallet inscribe --fee-rate 1 foo.png")
+    .write("foo.png", [1; 520])
+    .rpc_server(&rpc_server)
+    .output::<Inscribe>();
+
+  rpc_server.mine_blocks(1);
+
+  let output = CommandBuilder::new("brc20 --record-skipped index")
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  assert_eq!(output.skipped, 1);
+
+  let skipped = output.skipped_inscriptions.unwrap();
+  assert_eq!(skipped.len(), 1);
+  assert_eq!(skipped[0].inscription_id.to_string(), inscribe.inscription);
+  assert_eq!(skipped[0].reason, "Unsupported content type");
+}
+
+#[test]
+fn index_array_of_operations() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"[
+      {"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"},
+      {"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}
+    ]"#,
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 index")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      tickers: 1,
+      mints: 1,
+      transfers: 0,
+      invalid: 0,
+      skipped: 0,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+}
+
+#[test]
+fn index_array_rejects_repeated_mints_and_transfers() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+
+  inscribe_json(
+    &rpc_server,
+    r#"[
+      {"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"},
+      {"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"},
+      {"p":"brc-20","op":"transfer","tick":"ordi","amt":"10"}
+    ]"#,
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 index")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      tickers: 1,
+      mints: 1,
+      transfers: 0,
+      invalid: 2,
+      skipped: 0,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+}
+
+#[test]
+fn balance_at_height() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  let before_send: u64 = rpc_server.mine_blocks(1)[0].header.time.into();
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  let balance = |address: &str, at_height: Option<u64>| {
+    CommandBuilder::new(match at_height {
+      Some(height) => format!("brc20 balance --address {address} --at-height {height}"),
+      None => format!("brc20 balance --address {address}"),
+    })
+    .rpc_server(&rpc_server)
+    .output::<balance::Output>()
+    .tickers
+  };
+
+  assert_eq!(
+    balance(DESTINATION, Some(1)),
+    [("ordi".into(), "0".into())].into()
+  );
+  assert_eq!(
+    balance(DESTINATION, Some(before_send)),
+    [("ordi".into(), "1000".into())].into()
+  );
+  assert_eq!(
+    balance(DESTINATION, None),
+    [("ordi".into(), "600".into())].into()
+  );
+
+  assert_eq!(
+    balance(RECEIVER, Some(before_send)),
+    [("ordi".into(), "0".into())].into()
+  );
+  assert_eq!(
+    balance(RECEIVER, None),
+    [("ordi".into(), "400".into())].into()
+  );
+}
+
+#[test]
+fn log_file_records_validation_outcomes() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1001"}"#,
+  );
+
+  let tempdir = TempDir::new().unwrap();
+  let log_file = tempdir.path().join("brc20.log");
+
+  CommandBuilder::new(format!("brc20 --log-file {} index", log_file.display()))
+    .rpc_server(&rpc_server)
+    .output::<Output>();
+
+  let entries = fs::read_to_string(&log_file)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+    .collect::<Vec<serde_json::Value>>();
+
+  assert_eq!(entries.len(), 2);
+
+  assert_eq!(entries[0]["op"], "deploy");
+  assert_eq!(entries[0]["tick"], "ordi");
+  assert_eq!(entries[0]["valid"], true);
+  assert_eq!(entries[0]["reason"], serde_json::Value::Null);
+
+  assert_eq!(entries[1]["op"], "mint");
+  assert_eq!(entries[1]["valid"], false);
+  assert_eq!(entries[1]["reason"], "Mint amount exceeds limit");
+}
+
+#[test]
+fn history_shows_sent_and_received_transfers() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  let txid = CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  let blocktime = rpc_server.mine_blocks(1)[0].header.time.into();
+
+  let event = Event {
+    direction: Direction::Send,
+    inscription_id: inscription.parse().unwrap(),
+    amount: "400.0".into(),
+    sat_point: format!("{}:0:0", txid.trim()).parse().unwrap(),
+    from: DESTINATION.parse().unwrap(),
+    to: RECEIVER.parse().unwrap(),
+    txid: txid.trim().parse().unwrap(),
+    blocktime,
+  };
+
+  assert_eq!(
+    CommandBuilder::new(format!("brc20 history --address {DESTINATION}"))
+      .rpc_server(&rpc_server)
+      .output::<history::Output>(),
+    history::Output {
+      address: DESTINATION.parse().unwrap(),
+      tickers: [("ordi".into(), vec![event.clone()])].into(),
+    }
+  );
+
+  assert_eq!(
+    CommandBuilder::new(format!("brc20 history --address {RECEIVER}"))
+      .rpc_server(&rpc_server)
+      .output::<history::Output>(),
+    history::Output {
+      address: RECEIVER.parse().unwrap(),
+      tickers: [(
+        "ordi".into(),
+        vec![Event {
+          direction: Direction::Receive,
+          ..event
+        }]
+      )]
+      .into(),
+    }
+  );
+}
+
+#[test]
+fn ticker_info_shows_stats() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"8"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"0.5"}"#,
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 ticker-info --ticker ORDI")
+      .rpc_server(&rpc_server)
+      .output::<ticker_info::Output>(),
+    ticker_info::Output {
+      ticker: "ordi".into(),
+      max_supply: "21000000.0".into(),
+      limit: "1000.0".into(),
+      decimals: 8,
+      deploy_inscription: inscription.parse().unwrap(),
+      deploy_inscription_number: 0,
+      raw: r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"8"}"#
+        .into(),
+      holders: 1,
+      total_minted: "1000.0".into(),
+      circulating_supply: "999.5".into(),
+      transferred: "0.0".into(),
+      active_transfers: 1,
+      first_mint_blocktime: Some(4),
+      last_mint_blocktime: Some(4),
+      holders_by_address_type: [("segwit".into(), 1)].into(),
+    }
+  );
+}
+
+#[test]
+fn list_tickers() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"sats","amt":"250"}"#,
+  );
+
+  let ordi = list::Output {
+    ticker: "ordi".into(),
+    max_supply: "1000".into(),
+    total_minted: "0".into(),
+    percent_minted: 0.0,
+    holders: 0,
+    deploy_blocktime: 2,
+  };
+
+  let sats = list::Output {
+    ticker: "sats".into(),
+    max_supply: "1000".into(),
+    total_minted: "250".into(),
+    percent_minted: 25.0,
+    holders: 1,
+    deploy_blocktime: 4,
+  };
+
+  assert_eq!(
+    CommandBuilder::new("brc20 list")
+      .rpc_server(&rpc_server)
+      .output::<Vec<list::Output>>(),
+    [sats.clone(), ordi.clone()]
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 list --sort deploy")
+      .rpc_server(&rpc_server)
+      .output::<Vec<list::Output>>(),
+    [ordi, sats]
+  );
+}
+
+#[test]
+fn pending_transfers() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe {
+    inscription: sent, ..
+  } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"100"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"200"}"#,
+  );
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {sent}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  assert_eq!(
+    CommandBuilder::new("brc20 pending --ticker ordi")
+      .rpc_server(&rpc_server)
+      .output::<pending::Output>(),
+    pending::Output {
+      ticker: "ordi".into(),
+      transfers: vec![pending::Transfer {
+        inscription_id: inscription.parse().unwrap(),
+        owner: DESTINATION.parse().unwrap(),
+        amount: "200".into(),
+        blocktime: 8,
+      }],
+    }
+  );
+}
+
+#[cfg(unix)]
+#[test]
+fn serve_answers_requests_over_unix_socket() {
+  use {
+    ord::subcommand::brc20::serve::{Balance, Holder, Response},
+    std::{
+      io::{BufRead, BufReader, Write},
+      os::unix::net::UnixStream,
+    },
+  };
+
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  let tempdir = TempDir::new().unwrap();
+  let socket = tempdir.path().join("brc20.sock");
+
+  let builder = CommandBuilder::new(format!("brc20 serve --socket {}", socket.display()))
+    .rpc_server(&rpc_server);
+
+  let mut child = builder.command().stderr(Stdio::null()).spawn().unwrap();
+
+  let stream = (0..100)
+    .find_map(|_| {
+      let stream = UnixStream::connect(&socket).ok();
+      if stream.is_none() {
+        thread::sleep(Duration::from_millis(50));
+      }
+      stream
+    })
+    .unwrap();
+
+  let mut writer = stream.try_clone().unwrap();
+  let mut reader = BufReader::new(stream);
+
+  let mut request = |request: String| {
+    writeln!(writer, "{request}").unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    serde_json::from_str::<Response>(&line).unwrap()
+  };
+
+  assert_eq!(
+    request(format!(
+      r#"{{"method":"balance","params":{{"address":"{DESTINATION}","ticker":"ORDI"}}}}"#
+    )),
+    Response::Result(
+      serde_json::to_value(Balance {
+        address: DESTINATION.parse().unwrap(),
+        ticker: "ordi".into(),
+        overall: "1000".into(),
+        available: "600".into(),
+        transferable: "400".into(),
+      })
+      .unwrap()
+    )
+  );
+
+  assert_eq!(
+    request(r#"{"method":"holders","params":{"ticker":"ordi"}}"#.into()),
+    Response::Result(
+      serde_json::to_value(vec![Holder {
+        address: DESTINATION.parse().unwrap(),
+        balance: "1000".into(),
+      }])
+      .unwrap()
+    )
+  );
+
+  let Response::Result(ticker_info) =
+    request(r#"{"method":"ticker-info","params":{"ticker":"ordi"}}"#.into())
+  else {
+    panic!("ticker-info request failed");
+  };
+  assert_eq!(
+    serde_json::from_value::<ticker_info::Output>(ticker_info)
+      .unwrap()
+      .active_transfers,
+    1
+  );
+
+  assert_eq!(
+    request(r#"{"method":"ticker-info","params":{"ticker":"sats"}}"#.into()),
+    Response::Error("ticker sats not found".into())
+  );
+
+  assert!(matches!(
+    request(r#"{"method":"burn"}"#.into()),
+    Response::Error(err) if err.starts_with("invalid request")
+  ));
+
+  child.kill().unwrap();
+  child.wait().unwrap();
+}
+
+#[test]
+fn ticker_info_of_unknown_ticker_is_an_error() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+
+  CommandBuilder::new("brc20 ticker-info --ticker ordi")
+    .rpc_server(&rpc_server)
+    .expected_stderr("error: ticker ordi not found\n")
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn transfer_is_credited_to_first_receiver() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  for destination in [RECEIVER, DESTINATION] {
+    CommandBuilder::new(format!(
+      "wallet send --fee-rate 1 {destination} {inscription}"
+    ))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+    rpc_server.mine_blocks(1);
+  }
+
+  let output = CommandBuilder::new(format!("brc20 history --address {RECEIVER}"))
+    .rpc_server(&rpc_server)
+    .output::<history::Output>();
+
+  assert_eq!(output.tickers["ordi"].len(), 1);
+  assert_eq!(output.tickers["ordi"][0].direction, Direction::Receive);
+  assert_eq!(output.tickers["ordi"][0].to, RECEIVER.parse().unwrap());
+}
+
+#[test]
+fn deploy_two_mints_and_sent_transfer() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let Inscribe {
+    inscription: deploy,
+    ..
+  } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"500"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  assert_eq!(
+    CommandBuilder::new("brc20 index")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      tickers: 1,
+      mints: 2,
+      transfers: 1,
+      invalid: 0,
+      skipped: 0,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 ticker-info --ticker ordi")
+      .rpc_server(&rpc_server)
+      .output::<ticker_info::Output>(),
+    ticker_info::Output {
+      ticker: "ordi".into(),
+      max_supply: "21000000".into(),
+      limit: "1000".into(),
+      decimals: 0,
+      deploy_inscription: deploy.parse().unwrap(),
+      deploy_inscription_number: 0,
+      raw: r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#
+        .into(),
+      holders: 2,
+      total_minted: "1500".into(),
+      circulating_supply: "1500".into(),
+      transferred: "400".into(),
+      active_transfers: 0,
+      first_mint_blocktime: Some(4),
+      last_mint_blocktime: Some(6),
+      holders_by_address_type: [("segwit".into(), 2)].into(),
+    }
+  );
+
+  let sender = CommandBuilder::new(format!("brc20 history --address {DESTINATION}"))
+    .rpc_server(&rpc_server)
+    .output::<history::Output>();
+
+  assert_eq!(sender.tickers["ordi"].len(), 1);
+  assert_eq!(sender.tickers["ordi"][0].direction, Direction::Send);
+  assert_eq!(sender.tickers["ordi"][0].amount, "400");
+
+  let receiver = CommandBuilder::new(format!("brc20 history --address {RECEIVER}"))
+    .rpc_server(&rpc_server)
+    .output::<history::Output>();
+
+  assert_eq!(receiver.tickers["ordi"].len(), 1);
+  assert_eq!(receiver.tickers["ordi"][0].direction, Direction::Receive);
+  assert_eq!(receiver.tickers["ordi"][0].amount, "400");
+}
+
+#[test]
+fn unlisted_tickers_are_not_indexed() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"sats","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"sats","amt":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"pepe","amt":"1000"}"#,
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 --tickers ORDI,pepe index")
+      .rpc_server(&rpc_server)
+      .output::<Output>(),
+    Output {
+      tickers: 1,
+      mints: 0,
+      transfers: 0,
+      invalid: 1,
+      skipped: 2,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+
+  CommandBuilder::new("brc20 --tickers ordi ticker-info --ticker sats")
+    .rpc_server(&rpc_server)
+    .expected_stderr("error: ticker sats not found\n")
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn audit_finds_no_differences() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  assert_eq!(
+    CommandBuilder::new("brc20 audit")
+      .rpc_server(&rpc_server)
+      .output::<audit::Output>(),
+    audit::Output {
+      differences: Vec::new(),
+    }
+  );
+}
+
+#[test]
+fn compare_reports_mismatches_with_reference() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+
+  let reference = format!(
+    r#"{{
+      "tickers": {{
+        "ORDI": {{
+          "max_supply": 21000000.0,
+          "decimals": 18,
+          "total_minted": 1000.0,
+          "balances": {{ "{DESTINATION}": {{ "overall_balance": 900.0 }} }}
+        }},
+        "pepe": {{ "max_supply": 100.0, "decimals": 0, "total_minted": 0.0 }}
+      }}
+    }}"#
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 compare --reference reference.json")
+      .write("reference.json", reference)
+      .rpc_server(&rpc_server)
+      .output::<compare::Output>(),
+    compare::Output {
+      mismatches: vec![
+        Mismatch {
+          ticker: "ordi".into(),
+          field: "balance".into(),
+          address: Some(DESTINATION.parse().unwrap()),
+          computed: Some("1000.0".into()),
+          reference: Some("900.0".into()),
+        },
+        Mismatch {
+          ticker: "pepe".into(),
+          field: "max_supply".into(),
+          address: None,
+          computed: None,
+          reference: Some("100".into()),
+        },
+        Mismatch {
+          ticker: "pepe".into(),
+          field: "total_minted".into(),
+          address: None,
+          computed: None,
+          reference: Some("0".into()),
+        },
+      ],
+    }
+  );
+}
+
+#[test]
+fn mint_chart_shows_mints_per_block() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+
+  for amt in [1000, 500, 250] {
+    inscribe_json(
+      &rpc_server,
+      &format!(r#"{{"p":"brc-20","op":"mint","tick":"ordi","amt":"{amt}"}}"#),
+    );
+  }
+
+  assert_eq!(
+    CommandBuilder::new("brc20 mint-chart --ticker ordi")
+      .rpc_server(&rpc_server)
+      .output::<mint_chart::Output>(),
+    mint_chart::Output {
+      ticker: "ordi".into(),
+      mints: vec![
+        mint_chart::Point {
+          blocktime: 4,
+          minted: "1000".into(),
+        },
+        mint_chart::Point {
+          blocktime: 6,
+          minted: "500".into(),
+        },
+        mint_chart::Point {
+          blocktime: 8,
+          minted: "250".into(),
+        },
+      ],
+    }
+  );
+}
+
+#[test]
+fn diff_blocks_shows_changes_between_heights() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+
+  let after_mint: u64 = rpc_server.mine_blocks(1)[0].header.time.into();
+
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  let after_send: u64 = rpc_server.mine_blocks(1)[0].header.time.into();
+
+  assert_eq!(
+    CommandBuilder::new(format!("brc20 diff-blocks --from 1 --to {after_mint}"))
+      .rpc_server(&rpc_server)
+      .output::<diff_blocks::Output>(),
+    diff_blocks::Output {
+      from: 1,
+      to: after_mint,
+      new_tickers: vec!["ordi".into()],
+      balance_changes: [("ordi".into(), [(DESTINATION.into(), "1000".into())].into())].into(),
+      new_transfers: Vec::new(),
+    }
+  );
+
+  assert_eq!(
+    CommandBuilder::new(format!(
+      "brc20 diff-blocks --from {after_mint} --to {after_send}"
+    ))
+    .rpc_server(&rpc_server)
+    .output::<diff_blocks::Output>(),
+    diff_blocks::Output {
+      from: after_mint,
+      to: after_send,
+      new_tickers: Vec::new(),
+      balance_changes: [(
+        "ordi".into(),
+        [
+          (DESTINATION.into(), "-400".into()),
+          (RECEIVER.into(), "400".into()),
+        ]
+        .into()
+      )]
+      .into(),
+      new_transfers: vec![diff_blocks::Transfer {
+        ticker: "ordi".into(),
+        inscription_id: inscription.parse().unwrap(),
+        sender: DESTINATION.parse().unwrap(),
+        amount: "400".into(),
+      }],
+    }
+  );
+
+  CommandBuilder::new(format!(
+    "brc20 diff-blocks --from {after_send} --to {after_mint}"
+  ))
+  .rpc_server(&rpc_server)
+  .expected_stderr(format!(
+    "error: --from {after_send} is above --to {after_mint}\n"
+  ))
+  .expected_exit_code(1)
+  .run();
+
+  CommandBuilder::new("brc20 diff-blocks --from 1 --to 1000")
+    .rpc_server(&rpc_server)
+    .expected_stderr("error: block 1000 not found\n")
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn fees_sum_reveal_fees_of_ticker() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  let deploy = r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#;
+  let mint = r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#;
+
+  inscribe_json(&rpc_server, deploy);
+  inscribe_json(&rpc_server, mint);
+
+  let output = CommandBuilder::new("brc20 fees --ticker ordi")
+    .rpc_server(&rpc_server)
+    .output::<fees::Output>();
+
+  assert_eq!(
+    output,
+    fees::Output {
+      ticker: "ordi".into(),
+      inscriptions: 2,
+      total_size: deploy.len() + mint.len(),
+      total_fee: 279,
+      average_fee_rate: None,
+    }
+  );
+}
+
+#[test]
+fn snapshot_exports_holders_as_csv() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000","dec":"0"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"100"}"#,
+  );
+
+  let tempdir = TempDir::new().unwrap();
+  let csv = tempdir.path().join("holders.csv");
+
+  assert_eq!(
+    CommandBuilder::new(format!(
+      "brc20 snapshot --ticker ordi --output {}",
+      csv.display()
+    ))
+    .rpc_server(&rpc_server)
+    .output::<snapshot::Output>(),
+    snapshot::Output {
+      ticker: "ordi".into(),
+      holders: 2,
+    }
+  );
+
+  assert_eq!(
+    fs::read_to_string(csv).unwrap(),
+    format!(
+      "address,overall_balance,available_balance,transferable_balance\n\
+       {DESTINATION},600,500,100\n\
+       {RECEIVER},400,400,0\n"
+    )
+  );
+}
+
+#[test]
+fn at_resolves_satpoint_to_mint() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ORDI","max":"21000000","lim":"1000"}"#,
+  );
+  let Inscribe {
+    inscription,
+    reveal,
+    ..
+  } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+
+  assert_eq!(
+    CommandBuilder::new(format!("brc20 at --satpoint {reveal}:0:0"))
+      .rpc_server(&rpc_server)
+      .output::<at::Output>(),
+    at::Output {
+      satpoint: format!("{reveal}:0:0").parse().unwrap(),
+      inscription_id: inscription.parse().unwrap(),
+      op: "mint".into(),
+      ticker: "ORDI".into(),
+      amount: "1000.0".into(),
+      owner: DESTINATION.parse().unwrap(),
+      receiver: None,
+    }
+  );
+
+  CommandBuilder::new(format!("brc20 at --satpoint {reveal}:0:1"))
+    .rpc_server(&rpc_server)
+    .expected_stderr(format!("error: no BRC-20 operation at {reveal}:0:1\n"))
+    .expected_exit_code(1)
+    .run();
+}
+
+#[test]
+fn invalid_lists_rejected_operations() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  let over_limit = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ORDI","amt":"1001"}"#,
+  );
+  let undeployed = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"pepe","amt":"1"}"#,
+  );
+
+  let over_limit = InvalidTx {
+    inscription_id: over_limit.inscription.parse().unwrap(),
+    txid: over_limit.reveal,
+    op: "mint".into(),
+    ticker: "ORDI".into(),
+    reason: "Mint amount exceeds limit".into(),
+    blocktime: 4,
+  };
+
+  assert_eq!(
+    CommandBuilder::new("brc20 invalid")
+      .rpc_server(&rpc_server)
+      .output::<invalid::Output>(),
+    invalid::Output {
+      invalid: vec![
+        over_limit.clone(),
+        InvalidTx {
+          inscription_id: undeployed.inscription.parse().unwrap(),
+          txid: undeployed.reveal,
+          op: "mint".into(),
+          ticker: "pepe".into(),
+          reason: "Ticker not found: pepe".into(),
+          blocktime: 6,
+        },
+      ],
+    }
+  );
+
+  assert_eq!(
+    CommandBuilder::new("brc20 invalid --ticker ordi")
+      .rpc_server(&rpc_server)
+      .output::<invalid::Output>(),
+    invalid::Output {
+      invalid: vec![over_limit],
+    }
+  );
+}
+
+#[test]
+fn movements_show_balance_changes() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"21000000","lim":"1000"}"#,
+  );
+  let mint = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+
+  let txid = CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run()
+    .trim()
+    .parse()
+    .unwrap();
+
+  let blocktime = rpc_server.mine_blocks(1)[0].header.time.into();
+
+  let minted = Movement {
+    address: DESTINATION.parse().unwrap(),
+    ticker: "ordi".into(),
+    delta: "1000.0".into(),
+    new_balance: "1000.0".into(),
+    cause: Cause::Mint,
+    txid: mint.reveal,
+    blocktime: 4,
+  };
+
+  let sent = Movement {
+    address: DESTINATION.parse().unwrap(),
+    ticker: "ordi".into(),
+    delta: "-400.0".into(),
+    new_balance: "600.0".into(),
+    cause: Cause::Send,
+    txid,
+    blocktime,
+  };
+
+  let received = Movement {
+    address: RECEIVER.parse().unwrap(),
+    ticker: "ordi".into(),
+    delta: "400.0".into(),
+    new_balance: "400.0".into(),
+    cause: Cause::Receive,
+    txid,
+    blocktime,
+  };
+
+  assert_eq!(
+    CommandBuilder::new("brc20 movements")
+      .rpc_server(&rpc_server)
+      .output::<movements::Output>(),
+    movements::Output {
+      movements: vec![minted.clone(), sent.clone(), received],
+    }
+  );
+
+  assert_eq!(
+    CommandBuilder::new(format!(
+      "brc20 movements --address {DESTINATION} --ticker ORDI"
+    ))
+    .rpc_server(&rpc_server)
+    .output::<movements::Output>(),
+    movements::Output {
+      movements: vec![minted, sent],
+    }
+  );
+}
+
+#[test]
+fn sharded_index_matches_unsharded_index() {
+  let rpc_server = test_bitcoincore_rpc::spawn();
+  create_wallet(&rpc_server);
+
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"deploy","tick":"ordi","max":"1500","lim":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  let Inscribe { inscription, .. } = inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"transfer","tick":"ordi","amt":"400"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1000"}"#,
+  );
+  inscribe_json(
+    &rpc_server,
+    r#"{"p":"brc-20","op":"mint","tick":"ordi","amt":"1"}"#,
+  );
+
+  CommandBuilder::new(format!("wallet send --fee-rate 1 {RECEIVER} {inscription}"))
+    .rpc_server(&rpc_server)
+    .stdout_regex(".*")
+    .run();
+
+  rpc_server.mine_blocks(1);
+
+  let index = |shards: usize| {
+    CommandBuilder::new(format!("brc20 --shards {shards} index"))
+      .rpc_server(&rpc_server)
+      .output::<Output>()
+  };
+
+  let movements = |shards: usize| {
+    CommandBuilder::new(format!("brc20 --shards {shards} movements"))
+      .rpc_server(&rpc_server)
+      .output::<movements::Output>()
+  };
+
+  assert_eq!(
+    index(1),
+    Output {
+      tickers: 1,
+      mints: 2,
+      transfers: 1,
+      invalid: 1,
+      skipped: 0,
+      missing: 0,
+      skipped_inscriptions: None,
+    }
+  );
+
+  for shards in [2, 3, 5] {
+    assert_eq!(index(shards), index(1));
+    assert_eq!(movements(shards), movements(1));
+  }
+}
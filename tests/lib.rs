@@ -69,6 +69,7 @@ fn create_wallet(rpc_server: &test_bitcoincore_rpc::Handle) {
     .output::<Create>();
 }
 
+mod brc20;
 mod command_builder;
 mod core;
 mod epochs;